@@ -1,7 +1,7 @@
 use egui_demo_lib::{ColorTest, DemoWindows};
 use glfw::{self, Context};
 
-use egui_glfw::{egui, EguiBackend};
+use egui_glfw::{egui, EguiBackend, GlfwClipboard};
 
 /// Application.
 pub struct Application {
@@ -138,6 +138,7 @@ fn main() {
     }
 
     let mut egui = EguiBackend::new(&mut window, &mut glfw);
+    egui.set_clipboard(Some(Box::new(GlfwClipboard::new())));
 
     egui_extras::install_image_loaders(egui.get_egui_ctx());
 
@@ -152,43 +153,6 @@ fn main() {
 
         glfw::flush_messages(&events).for_each(|(_, event)| {
             egui.handle_event(&event, &window);
-
-            match event {
-                glfw::WindowEvent::Key(
-                    glfw::Key::X,
-                    _,
-                    glfw::Action::Press,
-                    glfw::Modifiers::Control,
-                ) => {
-                    egui.push_event(egui::Event::Cut);
-                }
-                glfw::WindowEvent::Key(
-                    glfw::Key::C,
-                    _,
-                    glfw::Action::Press,
-                    glfw::Modifiers::Control,
-                ) => {
-                    egui.push_event(egui::Event::Copy);
-                }
-                glfw::WindowEvent::Key(
-                    glfw::Key::V,
-                    _,
-                    glfw::Action::Press,
-                    glfw::Modifiers::Control,
-                ) => {
-                    let text = match copypasta_ext::try_context() {
-                        Some(mut context) => Some(context.get_contents().unwrap()),
-                        None => {
-                            eprintln!("enable to gather context for clipboard");
-                            None
-                        }
-                    };
-                    if let Some(text) = text {
-                        egui.push_event(egui::Event::Text(text));
-                    }
-                }
-                _ => {}
-            }
         });
 
         unsafe {
@@ -214,22 +178,8 @@ fn main() {
         });
 
         let (width, height) = window.get_framebuffer_size();
-        let output = egui.end_pass((width as _, height as _));
-
-        output
-            .platform_output
-            .commands
-            .into_iter()
-            .filter_map(|output| match output {
-                egui::OutputCommand::CopyText(text) => Some(text),
-                egui::OutputCommand::CopyImage(_) | egui::OutputCommand::OpenUrl(_) => None,
-            })
-            .for_each(|text| match copypasta_ext::try_context() {
-                Some(mut context) => context.set_contents(text).unwrap(),
-                None => {
-                    eprintln!("enable to gather context for clipboard");
-                }
-            });
+        egui.end_pass((width as _, height as _));
+        egui.apply_viewport_commands(&mut window);
 
         window.swap_buffers();
     }
@@ -0,0 +1,107 @@
+//! Record the input to a replay file and play it back.
+//!
+//! ```sh
+//! cargo run --example replay --features replay -- record input.replay
+//! cargo run --example replay --features replay -- play input.replay
+//! ```
+//!
+//! Playing exits once the replay ends, so that it can run in CI under
+//! a software OpenGL context, for example with
+//! `LIBGL_ALWAYS_SOFTWARE=1 xvfb-run cargo run ...`.
+
+use glfw::{self, Context};
+
+use egui_glfw::{egui, EguiBackend, InputPlayer, InputRecorder, MemoryClipboard};
+
+fn main() {
+    let args = std::env::args().collect::<Vec<_>>();
+    let (mode, path) = match args.as_slice() {
+        [_, mode, path] if mode == "record" || mode == "play" => (mode.as_str(), path),
+        _ => {
+            eprintln!("usage: replay (record|play) <path>");
+            std::process::exit(2);
+        }
+    };
+
+    let mut glfw = glfw::init(glfw::fail_on_errors).unwrap();
+
+    // set to opengl 3.3 or higher
+    glfw.window_hint(glfw::WindowHint::ContextVersion(3, 3));
+    glfw.window_hint(glfw::WindowHint::OpenGlProfile(
+        glfw::OpenGlProfileHint::Core,
+    ));
+    // the replay is faithful only for the recorded window size
+    glfw.window_hint(glfw::WindowHint::Resizable(false));
+    #[cfg(target_os = "macos")]
+    glfw.window_hint(glfw::WindowHint::OpenGlForwardCompat(true));
+    let (mut window, events) = glfw
+        .create_window(800, 600, "Replay", glfw::WindowMode::Windowed)
+        .expect("Failed to create glfw window");
+
+    window.set_all_polling(true);
+    window.make_current();
+
+    let mut egui = EguiBackend::new(&mut window, &mut glfw);
+    // the clipboard of the system would make the pastes differ
+    egui.set_clipboard(Some(Box::new(MemoryClipboard::new())));
+
+    let mut player = if mode == "record" {
+        egui.set_input_recorder(Some(
+            InputRecorder::create(path).expect("Failed to create the replay"),
+        ));
+        None
+    } else {
+        Some(InputPlayer::open(path).expect("Failed to open the replay"))
+    };
+
+    let mut text = String::from("hello");
+    let mut value = 0.5;
+
+    while !window.should_close() {
+        glfw.poll_events();
+
+        match player.as_mut() {
+            Some(player) => {
+                // the events of the window are ignored while playing
+                glfw::flush_messages(&events).for_each(drop);
+                if !player.play_frame(&mut egui, &window) {
+                    break;
+                }
+            }
+            None => glfw::flush_messages(&events).for_each(|(_, event)| {
+                egui.handle_event(&event, &window);
+            }),
+        }
+
+        unsafe {
+            gl::ClearColor(0.1, 0.3, 0.2, 1.0);
+            gl::Clear(gl::COLOR_BUFFER_BIT);
+        }
+
+        egui.begin_pass(&window, &mut glfw);
+
+        egui::CentralPanel::default().show(egui.get_egui_ctx(), |ui| {
+            ui.heading(if player.is_some() {
+                "Playing"
+            } else {
+                "Recording"
+            });
+            ui.text_edit_singleline(&mut text);
+            ui.add(egui::Slider::new(&mut value, 0.0..=1.0));
+        });
+
+        let (width, height) = window.get_framebuffer_size();
+        egui.end_pass((width as _, height as _));
+
+        window.swap_buffers();
+    }
+
+    if let Some(player) = player {
+        println!(
+            "played {} frames: text {:?} value {}",
+            player.get_frames().len(),
+            text,
+            value
+        );
+    }
+}
@@ -1,7 +1,7 @@
 use glfw::{self, Context};
 use nalgebra_glm as glm;
 
-use egui_glfw::{egui, EguiBackend, TextureRGBA8};
+use egui_glfw::{egui, EguiBackend, GlfwClipboard, TextureRGBA8};
 
 fn main() {
     let mut glfw = glfw::init(glfw::fail_on_errors).unwrap();
@@ -46,6 +46,7 @@ fn main() {
     }
 
     let mut egui = EguiBackend::new(&mut window, &mut glfw);
+    egui.set_clipboard(Some(Box::new(GlfwClipboard::new())));
 
     unsafe {
         gl::ClearColor(0.1, 0.3, 0.2, 1.0);
@@ -55,11 +56,15 @@ fn main() {
     let mut texture_param_r = 1.25;
     let mut texture_width = 300;
     let mut texture_height = 200;
-    let mut texture = generate_texture(
-        texture_width,
-        texture_height,
-        texture_param_t,
-        texture_param_r,
+    let mut texture_size = egui::vec2(texture_width as _, texture_height as _);
+    let texture_id = egui.register_texture(
+        generate_texture(
+            texture_width,
+            texture_height,
+            texture_param_t,
+            texture_param_r,
+        ),
+        egui::TextureOptions::NEAREST,
     );
 
     let mut inspection_window = true;
@@ -73,43 +78,6 @@ fn main() {
 
         glfw::flush_messages(&events).for_each(|(_, event)| {
             egui.handle_event(&event, &window);
-
-            match event {
-                glfw::WindowEvent::Key(
-                    glfw::Key::X,
-                    _,
-                    glfw::Action::Press,
-                    glfw::Modifiers::Control,
-                ) => {
-                    egui.push_event(egui::Event::Cut);
-                }
-                glfw::WindowEvent::Key(
-                    glfw::Key::C,
-                    _,
-                    glfw::Action::Press,
-                    glfw::Modifiers::Control,
-                ) => {
-                    egui.push_event(egui::Event::Copy);
-                }
-                glfw::WindowEvent::Key(
-                    glfw::Key::V,
-                    _,
-                    glfw::Action::Press,
-                    glfw::Modifiers::Control,
-                ) => {
-                    let text = match copypasta_ext::try_context() {
-                        Some(mut context) => Some(context.get_contents().unwrap()),
-                        None => {
-                            eprintln!("enable to gather context for clipboard");
-                            None
-                        }
-                    };
-                    if let Some(text) = text {
-                        egui.push_event(egui::Event::Text(text));
-                    }
-                }
-                _ => {}
-            }
         });
 
         unsafe {
@@ -198,44 +166,32 @@ fn main() {
                 ui.label(format!("wrote: {}", text_input_test));
             });
 
-        egui::Window::new("User Texture Test Window").show(egui.get_egui_ctx(), |ui| {
+        egui::Window::new("User Texture Test Window").show(&egui.get_egui_ctx().clone(), |ui| {
             ui.add(egui::Slider::new(&mut texture_width, 0..=512).text("Image Width"));
             ui.add(egui::Slider::new(&mut texture_height, 0..=512).text("Image Height"));
             ui.add(egui::Slider::new(&mut texture_param_t, 0.0..=1.0).text("Parameter 1"));
             ui.add(egui::Slider::new(&mut texture_param_r, 0.0..=1.0).text("Parameter 2"));
 
             if ui.button("Generate Texture").clicked() {
-                texture = generate_texture(
-                    texture_width,
-                    texture_height,
-                    texture_param_t,
-                    texture_param_r,
+                egui.update_texture(
+                    texture_id,
+                    generate_texture(
+                        texture_width,
+                        texture_height,
+                        texture_param_t,
+                        texture_param_r,
+                    ),
+                    egui::TextureOptions::NEAREST,
                 );
+                texture_size = egui::vec2(texture_width as _, texture_height as _);
             }
 
-            ui.image((
-                egui::TextureId::User(texture.get_gl_tex().into()),
-                egui::vec2(texture.get_width() as _, texture.get_height() as _),
-            ));
+            ui.image((texture_id, texture_size));
         });
 
         let (width, height) = window.get_framebuffer_size();
-        let output = egui.end_pass((width as _, height as _));
-
-        output
-            .platform_output
-            .commands
-            .into_iter()
-            .filter_map(|output| match output {
-                egui::OutputCommand::CopyText(text) => Some(text),
-                egui::OutputCommand::CopyImage(_) | egui::OutputCommand::OpenUrl(_) => None,
-            })
-            .for_each(|text| match copypasta_ext::try_context() {
-                Some(mut context) => context.set_contents(text).unwrap(),
-                None => {
-                    eprintln!("enable to gather context for clipboard");
-                }
-            });
+        egui.end_pass((width as _, height as _));
+        egui.apply_viewport_commands(&mut window);
 
         window.swap_buffers();
     }
@@ -0,0 +1,499 @@
+use std::ops::Range;
+
+use egui::epaint::ahash::AHashMap;
+
+use crate::texture::{PixelRetention, TextureRGBA8};
+
+/// Size (width and height) of a page of the [`TextureAtlas`] in
+/// pixels.
+pub const ATLAS_PAGE_SIZE: usize = 1024;
+
+/// Maximum size (width and height) of an image that is packed into
+/// the [`TextureAtlas`] in pixels.
+pub const ATLAS_MAX_IMAGE_SIZE: usize = 128;
+
+/// Padding around each image in the page. The edge pixels of the
+/// image are repeated in the padding so that linear filtering does
+/// not bleed the neighbouring images.
+const PADDING: usize = 1;
+
+/// Region of an image in the [`TextureAtlas`].
+#[derive(Debug, Clone, Copy)]
+struct AtlasRegion {
+    /// Index of the page the image is in.
+    page: usize,
+    /// Top left of the block of the padded image in the page.
+    pos: [usize; 2],
+    /// Size of the block of the padded image in the page.
+    size: [usize; 2],
+    /// UV rect of the image within the page, (0.0, 0.0) is the top
+    /// left similar to egui.
+    uv_rect: egui::Rect,
+}
+
+/// Row of blocks in a page, all blocks in the row are at most as
+/// tall as the row.
+#[derive(Debug)]
+struct Shelf {
+    /// Top of the shelf.
+    y: usize,
+    /// Height of the shelf.
+    height: usize,
+    /// Left of the free space at the end of the shelf.
+    next_x: usize,
+    /// Freed spans of the shelf before `next_x`, sorted and not
+    /// adjacent to each other.
+    free_spans: Vec<Range<usize>>,
+}
+
+impl Shelf {
+    /// Create a new empty [`Shelf`].
+    fn new(y: usize, height: usize) -> Self {
+        Self {
+            y,
+            height,
+            next_x: 0,
+            free_spans: Vec::new(),
+        }
+    }
+
+    /// Find the left of a free span of at least `width`, preferring
+    /// the freed spans over the free space at the end.
+    fn find_space(&self, width: usize) -> Option<usize> {
+        self.free_spans
+            .iter()
+            .find(|span| span.len() >= width)
+            .map(|span| span.start)
+            .or_else(|| (ATLAS_PAGE_SIZE - self.next_x >= width).then_some(self.next_x))
+    }
+
+    /// Take the span of `width` at `x` found by
+    /// [`Self::find_space()`].
+    fn take(&mut self, x: usize, width: usize) {
+        if x == self.next_x {
+            self.next_x += width;
+            return;
+        }
+        let index = self
+            .free_spans
+            .iter()
+            .position(|span| span.start == x)
+            .unwrap();
+        self.free_spans[index].start += width;
+        if self.free_spans[index].is_empty() {
+            self.free_spans.remove(index);
+        }
+    }
+
+    /// Free the span of `width` at `x`, merging it with the
+    /// neighbouring free spans.
+    fn free(&mut self, x: usize, width: usize) {
+        let index = self.free_spans.partition_point(|span| span.start < x);
+        self.free_spans.insert(index, x..(x + width));
+
+        // merge with the next span and then the previous span
+        if index + 1 < self.free_spans.len()
+            && self.free_spans[index].end == self.free_spans[index + 1].start
+        {
+            self.free_spans[index].end = self.free_spans.remove(index + 1).end;
+        }
+        let index = if index > 0 && self.free_spans[index - 1].end == self.free_spans[index].start {
+            self.free_spans[index - 1].end = self.free_spans.remove(index).end;
+            index - 1
+        } else {
+            index
+        };
+
+        // give the span at the end back to the free space at the end
+        if self.free_spans[index].end == self.next_x {
+            self.next_x = self.free_spans.remove(index).start;
+        }
+    }
+
+    /// Is the shelf empty.
+    fn is_empty(&self) -> bool {
+        self.next_x == 0
+    }
+}
+
+/// Packs blocks into a page on shelves.
+#[derive(Debug, Default)]
+struct ShelfPacker {
+    /// Shelves of the page from top to bottom.
+    shelves: Vec<Shelf>,
+}
+
+impl ShelfPacker {
+    /// Allocate a `width` by `height` block, returns the top left of
+    /// the block.
+    ///
+    /// Returns [`None`] if there is no space left.
+    fn allocate(&mut self, width: usize, height: usize) -> Option<[usize; 2]> {
+        // use the shortest shelf that fits the block to waste the
+        // least space
+        if let Some((shelf, x)) = self
+            .shelves
+            .iter_mut()
+            .filter(|shelf| shelf.height >= height)
+            .filter_map(|shelf| shelf.find_space(width).map(|x| (shelf, x)))
+            .min_by_key(|(shelf, _)| shelf.height)
+        {
+            shelf.take(x, width);
+            return Some([x, shelf.y]);
+        }
+
+        // start a new shelf below the last one
+        let y = self
+            .shelves
+            .last()
+            .map_or(0, |shelf| shelf.y + shelf.height);
+        if ATLAS_PAGE_SIZE - y < height || ATLAS_PAGE_SIZE < width {
+            return None;
+        }
+        let mut shelf = Shelf::new(y, height);
+        shelf.take(0, width);
+        self.shelves.push(shelf);
+        Some([0, y])
+    }
+
+    /// Free the block of `width` at `pos` allocated by
+    /// [`Self::allocate()`].
+    fn free(&mut self, pos: [usize; 2], width: usize) {
+        let shelf = self
+            .shelves
+            .iter_mut()
+            .find(|shelf| shelf.y == pos[1])
+            .expect("block must be allocated");
+        shelf.free(pos[0], width);
+
+        // the empty shelves at the bottom can be of any height again
+        while self.shelves.last().is_some_and(Shelf::is_empty) {
+            self.shelves.pop();
+        }
+    }
+}
+
+/// A page of the [`TextureAtlas`].
+struct AtlasPage {
+    /// Texture of the page.
+    texture: TextureRGBA8,
+    /// Packer of the images in the page.
+    packer: ShelfPacker,
+    /// Number of images in the page.
+    image_len: usize,
+}
+
+impl AtlasPage {
+    /// Create a new empty [`AtlasPage`].
+    fn new(texture_options: egui::TextureOptions) -> Self {
+        let mut texture = TextureRGBA8::from_pixels(
+            ATLAS_PAGE_SIZE,
+            ATLAS_PAGE_SIZE,
+            vec![(0, 0, 0, 0); ATLAS_PAGE_SIZE * ATLAS_PAGE_SIZE],
+            texture_options,
+        );
+        // the images are updated on the GPU only
+        texture.set_pixel_retention(PixelRetention::KeepUntilUploaded);
+        Self {
+            texture,
+            packer: ShelfPacker::default(),
+            image_len: 0,
+        }
+    }
+}
+
+/// Atlas that packs small images into shared pages so that the
+/// meshes that use them can be drawn together.
+///
+/// Images are identified by the id of their
+/// [`egui::TextureId::User`].
+#[derive(Default)]
+pub struct TextureAtlas {
+    /// Pages of the atlas, [`None`] for the removed pages so that the
+    /// regions can refer to the pages by index. Empty pages are
+    /// removed except the first one.
+    pages: Vec<Option<AtlasPage>>,
+    /// Regions of the images in the atlas.
+    regions: AHashMap<u64, AtlasRegion>,
+}
+
+impl TextureAtlas {
+    /// Create a new empty [`TextureAtlas`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Can an image of `size` sampled with `texture_options` be
+    /// packed into the atlas.
+    ///
+    /// Only small images that are not repeated and do not use
+    /// mipmaps can be packed.
+    pub fn supports(size: [usize; 2], texture_options: &egui::TextureOptions) -> bool {
+        (1..=ATLAS_MAX_IMAGE_SIZE).contains(&size[0])
+            && (1..=ATLAS_MAX_IMAGE_SIZE).contains(&size[1])
+            && texture_options.wrap_mode == egui::TextureWrapMode::ClampToEdge
+            && texture_options.mipmap_mode.is_none()
+    }
+
+    /// Pack `image` into the atlas as `id`. The image must be
+    /// supported, see [`Self::supports()`].
+    pub fn insert(
+        &mut self,
+        id: u64,
+        image: &egui::ColorImage,
+        texture_options: egui::TextureOptions,
+    ) {
+        assert!(Self::supports(image.size, &texture_options));
+        self.remove(id);
+
+        let [width, height] = image.size;
+        let padded_image = Self::pad_image(image);
+        let [padded_width, padded_height] = padded_image.size;
+
+        let allocation = self
+            .pages
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(index, page)| page.as_mut().map(|page| (index, page)))
+            .filter(|(_, page)| *page.texture.get_texture_options() == texture_options)
+            .find_map(|(index, page)| {
+                page.packer
+                    .allocate(padded_width, padded_height)
+                    .map(|pos| (index, pos))
+            });
+        let (page_index, pos) = allocation.unwrap_or_else(|| {
+            let mut page = AtlasPage::new(texture_options);
+            let pos = page.packer.allocate(padded_width, padded_height).unwrap();
+            // reuse the slot of a removed page
+            match self.pages.iter().position(Option::is_none) {
+                Some(index) => {
+                    self.pages[index] = Some(page);
+                    (index, pos)
+                }
+                None => {
+                    self.pages.push(Some(page));
+                    (self.pages.len() - 1, pos)
+                }
+            }
+        });
+
+        let page = self.pages[page_index].as_mut().unwrap();
+        page.texture
+            .update_from_egui(&egui::epaint::ImageDelta::partial(
+                pos,
+                padded_image,
+                texture_options,
+            ));
+        page.image_len += 1;
+
+        let page_size = ATLAS_PAGE_SIZE as f32;
+        self.regions.insert(
+            id,
+            AtlasRegion {
+                page: page_index,
+                pos,
+                size: [padded_width, padded_height],
+                uv_rect: egui::Rect::from_min_size(
+                    egui::pos2(
+                        (pos[0] + PADDING) as f32 / page_size,
+                        (pos[1] + PADDING) as f32 / page_size,
+                    ),
+                    egui::vec2(width as f32 / page_size, height as f32 / page_size),
+                ),
+            },
+        );
+    }
+
+    /// Remove the image `id` from the atlas, returns `true` if the
+    /// image was in the atlas.
+    pub fn remove(&mut self, id: u64) -> bool {
+        let Some(region) = self.regions.remove(&id) else {
+            return false;
+        };
+
+        // the stale pixels are overwritten by the next images
+        let page = self.pages[region.page].as_mut().unwrap();
+        page.packer.free(region.pos, region.size[0]);
+        page.image_len -= 1;
+        if page.image_len == 0 && region.page != 0 {
+            self.pages[region.page] = None;
+        }
+
+        true
+    }
+
+    /// Get the OpenGL texture name and [`egui::TextureOptions`] of
+    /// the page of the image `id` along with the UV rect of the
+    /// image within the page.
+    pub fn get_gl_tex(
+        &mut self,
+        id: u64,
+    ) -> Option<(gl::types::GLuint, egui::TextureOptions, egui::Rect)> {
+        let region = self.regions.get(&id)?;
+        let page = self.pages[region.page].as_mut().unwrap();
+        Some((
+            page.texture.get_gl_tex(),
+            *page.texture.get_texture_options(),
+            region.uv_rect,
+        ))
+    }
+
+    /// Get the number of pages of the atlas.
+    pub fn get_page_len(&self) -> usize {
+        self.pages.iter().flatten().count()
+    }
+
+    /// Get the number of bytes used by the pages on the GPU.
+    pub fn get_gpu_bytes(&self) -> usize {
+        self.pages
+            .iter()
+            .flatten()
+            .map(|page| page.texture.get_gpu_bytes())
+            .sum()
+    }
+
+    /// Get the number of bytes used by the pages on the CPU.
+    pub fn get_cpu_bytes(&self) -> usize {
+        self.pages
+            .iter()
+            .flatten()
+            .map(|page| page.texture.get_cpu_bytes())
+            .sum()
+    }
+
+    /// Pad the image by repeating the edge pixels.
+    fn pad_image(image: &egui::ColorImage) -> egui::ColorImage {
+        let [width, height] = image.size;
+        let mut padded_image = egui::ColorImage::new(
+            [width + 2 * PADDING, height + 2 * PADDING],
+            egui::Color32::TRANSPARENT,
+        );
+        for y in 0..padded_image.height() {
+            for x in 0..padded_image.width() {
+                padded_image[(x, y)] = image[(
+                    x.saturating_sub(PADDING).min(width - 1),
+                    y.saturating_sub(PADDING).min(height - 1),
+                )];
+            }
+        }
+        padded_image
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn packer_uses_the_shortest_shelf() {
+        let mut packer = ShelfPacker::default();
+        assert_eq!(packer.allocate(10, 10), Some([0, 0]));
+        assert_eq!(packer.allocate(20, 10), Some([10, 0]));
+        assert_eq!(packer.allocate(10, 20), Some([0, 10]));
+        assert_eq!(packer.allocate(5, 5), Some([30, 0]));
+        assert_eq!(packer.allocate(5, 15), Some([10, 10]));
+    }
+
+    #[test]
+    fn packer_overflow() {
+        let mut packer = ShelfPacker::default();
+        assert_eq!(packer.allocate(ATLAS_PAGE_SIZE + 1, 1), None);
+        assert_eq!(packer.allocate(1, ATLAS_PAGE_SIZE + 1), None);
+
+        let half = ATLAS_PAGE_SIZE / 2;
+        assert_eq!(packer.allocate(ATLAS_PAGE_SIZE, half), Some([0, 0]));
+        assert_eq!(packer.allocate(ATLAS_PAGE_SIZE, half), Some([0, half]));
+        assert_eq!(packer.allocate(1, 1), None);
+    }
+
+    #[test]
+    fn packer_reuses_freed_blocks() {
+        let mut packer = ShelfPacker::default();
+        let a = packer.allocate(10, 10).unwrap();
+        let b = packer.allocate(10, 10).unwrap();
+        let c = packer.allocate(10, 10).unwrap();
+        let d = packer.allocate(10, 10).unwrap();
+
+        packer.free(b, 10);
+        assert_eq!(packer.allocate(10, 5), Some(b));
+
+        // adjacent freed blocks are merged
+        packer.free(b, 10);
+        packer.free(c, 10);
+        assert_eq!(packer.shelves[0].free_spans, vec![10..30]);
+        assert_eq!(packer.allocate(15, 10), Some(b));
+        assert_eq!(packer.allocate(5, 10), Some([25, 0]));
+
+        // freeing the last block gives the space back to the end of
+        // the shelf
+        packer.free(d, 10);
+        assert_eq!(packer.shelves[0].next_x, 30);
+        packer.free(a, 10);
+        packer.free(b, 15);
+        packer.free([25, 0], 5);
+        assert!(packer.shelves.is_empty());
+
+        // the empty page fits blocks of any height again
+        assert_eq!(
+            packer.allocate(ATLAS_PAGE_SIZE, ATLAS_PAGE_SIZE),
+            Some([0, 0])
+        );
+    }
+
+    #[test]
+    fn pad_image_repeats_the_edges() {
+        let [a, b, c, d] = [
+            egui::Color32::RED,
+            egui::Color32::GREEN,
+            egui::Color32::BLUE,
+            egui::Color32::WHITE,
+        ];
+        let image = egui::ColorImage {
+            size: [2, 2],
+            pixels: vec![a, b, c, d],
+        };
+        let padded_image = TextureAtlas::pad_image(&image);
+        assert_eq!(padded_image.size, [4, 4]);
+        #[rustfmt::skip]
+        assert_eq!(
+            padded_image.pixels,
+            vec![
+                a, a, b, b,
+                a, a, b, b,
+                c, c, d, d,
+                c, c, d, d,
+            ]
+        );
+    }
+
+    #[test]
+    fn empty_pages_are_removed() {
+        let image = egui::ColorImage::new(
+            [ATLAS_MAX_IMAGE_SIZE, ATLAS_MAX_IMAGE_SIZE],
+            egui::Color32::RED,
+        );
+        let images_per_page = (ATLAS_PAGE_SIZE / (ATLAS_MAX_IMAGE_SIZE + 2 * PADDING)).pow(2);
+
+        let mut atlas = TextureAtlas::new();
+        (0..=images_per_page as u64).for_each(|id| {
+            atlas.insert(id, &image, egui::TextureOptions::LINEAR);
+        });
+        assert_eq!(atlas.get_page_len(), 2);
+
+        // the space of a removed image is reused
+        assert!(atlas.remove(0));
+        atlas.insert(0, &image, egui::TextureOptions::LINEAR);
+        assert_eq!(atlas.get_page_len(), 2);
+        assert_eq!(atlas.regions[&0].page, 0);
+
+        assert!(atlas.remove(images_per_page as u64));
+        assert_eq!(atlas.get_page_len(), 1);
+        assert!(!atlas.remove(images_per_page as u64));
+
+        // the first page is kept
+        (0..images_per_page as u64).for_each(|id| {
+            assert!(atlas.remove(id));
+        });
+        assert_eq!(atlas.get_page_len(), 1);
+    }
+}
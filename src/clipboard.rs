@@ -0,0 +1,305 @@
+use std::{cell::RefCell, ffi::CStr, rc::Rc};
+
+/// Clipboard used by the backend for cut, copy and paste, see
+/// [`crate::EguiBackend::set_clipboard()`].
+pub trait Clipboard {
+    /// Get the text in the clipboard.
+    fn get_text(&mut self) -> Option<String>;
+
+    /// Set the text in the clipboard.
+    fn set_text(&mut self, text: &str);
+
+    /// Set the image in the clipboard.
+    ///
+    /// Returns `false` if images are not supported by the clipboard.
+    fn set_image(&mut self, _image: &egui::ColorImage) -> bool {
+        false
+    }
+}
+
+/// [`Clipboard`] of the system through GLFW. Images are not
+/// supported.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct GlfwClipboard;
+
+impl GlfwClipboard {
+    /// Create a new [`GlfwClipboard`], GLFW must be initialized.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Clipboard for GlfwClipboard {
+    fn get_text(&mut self) -> Option<String> {
+        // GLFW does not use the window for the clipboard, same as
+        // `glfw::Window::get_clipboard_string()` without a window
+        unsafe {
+            let text = glfw::ffi::glfwGetClipboardString(std::ptr::null_mut());
+            if text.is_null() {
+                None
+            } else {
+                Some(CStr::from_ptr(text).to_string_lossy().into_owned())
+            }
+        }
+    }
+
+    fn set_text(&mut self, text: &str) {
+        // interior nul bytes cannot be passed to GLFW, the text is
+        // cut at the first one
+        let text = text.split('\0').next().unwrap_or_default();
+        let text = std::ffi::CString::new(text).unwrap();
+        unsafe {
+            glfw::ffi::glfwSetClipboardString(std::ptr::null_mut(), text.as_ptr());
+        }
+    }
+}
+
+/// Contents of a [`MemoryClipboard`].
+#[derive(Debug, Default)]
+struct MemoryClipboardContents {
+    text: Option<String>,
+    image: Option<egui::ColorImage>,
+}
+
+/// [`Clipboard`] that keeps its contents in memory, useful for
+/// tests. Clones share the contents so that a clone can be kept to
+/// inspect the clipboard given to the backend.
+#[derive(Debug, Default, Clone)]
+pub struct MemoryClipboard {
+    contents: Rc<RefCell<MemoryClipboardContents>>,
+}
+
+impl MemoryClipboard {
+    /// Create a new empty [`MemoryClipboard`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the image in the clipboard.
+    pub fn get_image(&self) -> Option<egui::ColorImage> {
+        self.contents.borrow().image.clone()
+    }
+}
+
+impl Clipboard for MemoryClipboard {
+    fn get_text(&mut self) -> Option<String> {
+        self.contents.borrow().text.clone()
+    }
+
+    fn set_text(&mut self, text: &str) {
+        let mut contents = self.contents.borrow_mut();
+        contents.text = Some(text.to_string());
+        contents.image = None;
+    }
+
+    fn set_image(&mut self, image: &egui::ColorImage) -> bool {
+        let mut contents = self.contents.borrow_mut();
+        contents.text = None;
+        contents.image = Some(image.clone());
+        true
+    }
+}
+
+/// Keyboard shortcuts for cut, copy and paste, see
+/// [`crate::EguiBackend::set_clipboard_bindings()`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClipboardBindings {
+    /// Shortcuts that cut.
+    pub cut: Vec<egui::KeyboardShortcut>,
+    /// Shortcuts that copy.
+    pub copy: Vec<egui::KeyboardShortcut>,
+    /// Shortcuts that paste.
+    pub paste: Vec<egui::KeyboardShortcut>,
+}
+
+impl Default for ClipboardBindings {
+    fn default() -> Self {
+        let shortcut = egui::KeyboardShortcut::new;
+        let mut bindings = Self {
+            cut: vec![
+                shortcut(egui::Modifiers::COMMAND, egui::Key::X),
+                shortcut(egui::Modifiers::NONE, egui::Key::Cut),
+            ],
+            copy: vec![
+                shortcut(egui::Modifiers::COMMAND, egui::Key::C),
+                shortcut(egui::Modifiers::NONE, egui::Key::Copy),
+            ],
+            paste: vec![
+                shortcut(egui::Modifiers::COMMAND, egui::Key::V),
+                shortcut(egui::Modifiers::NONE, egui::Key::Paste),
+            ],
+        };
+        if !cfg!(target_os = "macos") {
+            bindings
+                .cut
+                .push(shortcut(egui::Modifiers::SHIFT, egui::Key::Delete));
+            bindings
+                .copy
+                .push(shortcut(egui::Modifiers::CTRL, egui::Key::Insert));
+            bindings
+                .paste
+                .push(shortcut(egui::Modifiers::SHIFT, egui::Key::Insert));
+        }
+        bindings
+    }
+}
+
+/// Clipboard of the backend along with its bindings.
+#[derive(Default)]
+pub struct ClipboardIntegration {
+    /// Clipboard, cut, copy and paste are not handled without one.
+    pub clipboard: Option<Box<dyn Clipboard>>,
+    /// Bindings for cut, copy and paste.
+    pub bindings: ClipboardBindings,
+}
+
+impl ClipboardIntegration {
+    /// Get the [`egui::Event::Paste`] of the text in the clipboard.
+    pub fn paste_event(&mut self) -> Option<egui::Event> {
+        self.clipboard.as_mut()?.get_text().map(egui::Event::Paste)
+    }
+
+    /// Push [`egui::Event::Cut`], [`egui::Event::Copy`] and
+    /// [`egui::Event::Paste`] after the key presses of `raw_input`
+    /// that match the bindings.
+    pub fn process_input(&mut self, raw_input: &mut egui::RawInput) {
+        if self.clipboard.is_none() {
+            return;
+        }
+
+        let matches = |shortcuts: &[egui::KeyboardShortcut], key, modifiers: &egui::Modifiers| {
+            shortcuts.iter().any(|shortcut| {
+                shortcut.logical_key == key && modifiers.matches_exact(shortcut.modifiers)
+            })
+        };
+
+        let events = std::mem::take(&mut raw_input.events);
+        raw_input.events.reserve(events.len());
+        events.into_iter().for_each(|event| {
+            let clipboard_event = match &event {
+                egui::Event::Key {
+                    key,
+                    pressed: true,
+                    modifiers,
+                    ..
+                } => {
+                    if matches(&self.bindings.cut, *key, modifiers) {
+                        Some(egui::Event::Cut)
+                    } else if matches(&self.bindings.copy, *key, modifiers) {
+                        Some(egui::Event::Copy)
+                    } else if matches(&self.bindings.paste, *key, modifiers) {
+                        self.paste_event()
+                    } else {
+                        None
+                    }
+                }
+                _ => None,
+            };
+            raw_input.events.push(event);
+            raw_input.events.extend(clipboard_event);
+        });
+    }
+
+    /// Process the [`egui::OutputCommand::CopyText`] and
+    /// [`egui::OutputCommand::CopyImage`] of `commands`.
+    pub fn process_output(&mut self, commands: &[egui::OutputCommand]) {
+        let Some(clipboard) = self.clipboard.as_mut() else {
+            return;
+        };
+
+        commands.iter().for_each(|command| match command {
+            egui::OutputCommand::CopyText(text) => clipboard.set_text(text),
+            egui::OutputCommand::CopyImage(image) => {
+                if !clipboard.set_image(image) {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!("the clipboard does not support images");
+                }
+            }
+            egui::OutputCommand::OpenUrl(_) => {}
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Get a key press of `key` with `modifiers`.
+    fn key_press(key: egui::Key, modifiers: egui::Modifiers) -> egui::Event {
+        egui::Event::Key {
+            key,
+            physical_key: None,
+            pressed: true,
+            repeat: false,
+            modifiers,
+        }
+    }
+
+    /// Get a [`ClipboardIntegration`] with a [`MemoryClipboard`]
+    /// that shares its contents with the returned clone.
+    fn integration() -> (ClipboardIntegration, MemoryClipboard) {
+        let clipboard = MemoryClipboard::new();
+        (
+            ClipboardIntegration {
+                clipboard: Some(Box::new(clipboard.clone())),
+                bindings: ClipboardBindings::default(),
+            },
+            clipboard,
+        )
+    }
+
+    #[test]
+    fn bindings_push_clipboard_events() {
+        let (mut integration, mut clipboard) = integration();
+        clipboard.set_text("pasted");
+
+        let mut raw_input = egui::RawInput {
+            events: vec![
+                key_press(egui::Key::X, egui::Modifiers::COMMAND),
+                key_press(egui::Key::C, egui::Modifiers::COMMAND),
+                key_press(egui::Key::V, egui::Modifiers::COMMAND),
+                key_press(egui::Key::V, egui::Modifiers::NONE),
+            ],
+            ..Default::default()
+        };
+        integration.process_input(&mut raw_input);
+
+        assert_eq!(
+            raw_input.events,
+            vec![
+                key_press(egui::Key::X, egui::Modifiers::COMMAND),
+                egui::Event::Cut,
+                key_press(egui::Key::C, egui::Modifiers::COMMAND),
+                egui::Event::Copy,
+                key_press(egui::Key::V, egui::Modifiers::COMMAND),
+                egui::Event::Paste("pasted".to_string()),
+                key_press(egui::Key::V, egui::Modifiers::NONE),
+            ]
+        );
+    }
+
+    #[test]
+    fn no_clipboard_events_without_clipboard() {
+        let mut integration = ClipboardIntegration::default();
+        let events = vec![key_press(egui::Key::C, egui::Modifiers::COMMAND)];
+        let mut raw_input = egui::RawInput {
+            events: events.clone(),
+            ..Default::default()
+        };
+        integration.process_input(&mut raw_input);
+        assert_eq!(raw_input.events, events);
+    }
+
+    #[test]
+    fn output_sets_clipboard() {
+        let (mut integration, mut clipboard) = integration();
+
+        integration.process_output(&[egui::OutputCommand::CopyText("copied".to_string())]);
+        assert_eq!(clipboard.get_text().as_deref(), Some("copied"));
+
+        let image = egui::ColorImage::new([2, 2], egui::Color32::RED);
+        integration.process_output(&[egui::OutputCommand::CopyImage(image.clone())]);
+        assert_eq!(clipboard.get_image(), Some(image));
+        assert_eq!(clipboard.get_text(), None);
+    }
+}
@@ -0,0 +1,538 @@
+// Based on Blender's GPUBatch
+use std::convert::TryInto;
+
+use crate::gpu_immediate::{
+    gpu_buf_alloc, gpu_buf_free, gpu_vao_alloc, gpu_vao_free, GPUIndexType, GPUPrimType,
+    GPUVertFormat, GPU_VERT_ATTR_MAX_LEN,
+};
+use crate::shader::Shader;
+use crate::util::str_to_cstr;
+
+/// Vertices and indices of a [`GPUBatch`] recorded on the CPU
+/// before they are uploaded.
+struct GPUBatchData {
+    vertex_format: GPUVertFormat,
+
+    vertex_len: usize,
+    vertex_idx: usize,
+    vertex_data: Vec<u8>,
+    unassigned_attr_bits: u16, // which attributes of the current vertex have not been given values
+
+    index_type: Option<GPUIndexType>,
+    index_len: usize,
+    index_data: Vec<u8>,
+
+    /// Is the batch being recorded, i.e. between
+    /// [`GPUBatch::begin()`] and [`GPUBatch::end()`].
+    recording: bool,
+}
+
+impl GPUBatchData {
+    fn new() -> Self {
+        Self {
+            vertex_format: GPUVertFormat::new(),
+
+            vertex_len: 0,
+            vertex_idx: 0,
+            vertex_data: Vec::new(),
+            unassigned_attr_bits: 0,
+
+            index_type: None,
+            index_len: 0,
+            index_data: Vec::new(),
+
+            recording: false,
+        }
+    }
+
+    fn begin(&mut self, vertex_len: usize) {
+        assert_ne!(vertex_len, 0);
+        assert!(!self.recording);
+
+        self.vertex_format.ensure_packed();
+
+        self.vertex_len = vertex_len;
+        self.vertex_idx = 0;
+        self.vertex_data.clear();
+        self.vertex_data
+            .resize(self.vertex_format.vertex_buffer_size(vertex_len), 0);
+        self.unassigned_attr_bits = self.all_attr_bits();
+
+        self.index_type = None;
+        self.index_len = 0;
+        self.index_data.clear();
+
+        self.recording = true;
+    }
+
+    fn begin_indexed(&mut self, vertex_len: usize, index_type: GPUIndexType, index_len: usize) {
+        assert_ne!(index_len, 0);
+
+        self.begin(vertex_len);
+
+        self.index_type = Some(index_type);
+        self.index_len = index_len;
+        self.index_data.reserve(index_type.buffer_size(index_len));
+    }
+
+    /// Stop recording, all the promised vertices and indices must
+    /// have been recorded.
+    fn finish(&mut self) {
+        assert!(self.recording);
+        assert_eq!(self.vertex_idx, self.vertex_len, "number of verts is not equal to promised vertex_len; self.vertex_idx: {}, self.vertex_len: {}", self.vertex_idx, self.vertex_len);
+        if let Some(index_type) = self.index_type {
+            let index_idx = self.index_data.len() / index_type.size();
+            assert_eq!(index_idx, self.index_len, "number of indices is not equal to promised index_len; index_idx: {}, self.index_len: {}", index_idx, self.index_len);
+        }
+        self.recording = false;
+    }
+
+    fn all_attr_bits(&self) -> u16 {
+        (0..self.vertex_format.get_attrs_len()).fold(0, |bits, attr_index| bits | (1 << attr_index))
+    }
+
+    fn set_attr_value(&mut self, attr_id: usize, values: &[f32]) {
+        assert!(self.recording);
+        assert!(self.vertex_idx < self.vertex_len);
+
+        self.unassigned_attr_bits &= !(1 << attr_id);
+
+        let stride = self.vertex_format.get_stride();
+        let start = self.vertex_idx * stride;
+        self.vertex_format.set_attr_f32(
+            &mut self.vertex_data[start..(start + stride)],
+            attr_id,
+            values,
+        );
+    }
+
+    fn end_vertex(&mut self) {
+        let stride = self.vertex_format.get_stride();
+
+        // if all attributes haven't been assigned, take from previous vertex
+        if self.unassigned_attr_bits != 0 {
+            assert!(self.vertex_idx > 0);
+
+            for attr_index in 0..self.vertex_format.get_attrs_len() {
+                if (self.unassigned_attr_bits >> attr_index) & 1 != 0 {
+                    let (offset, size) = self.vertex_format.get_attr_offset_and_size(attr_index);
+                    let start = self.vertex_idx * stride + offset;
+                    self.vertex_data
+                        .copy_within((start - stride)..(start - stride + size), start);
+                }
+            }
+        }
+
+        self.vertex_idx += 1;
+        self.unassigned_attr_bits = self.all_attr_bits();
+    }
+
+    fn index(&mut self, index: u32) {
+        assert!(self.recording);
+        let index_type = self
+            .index_type
+            .expect("index() must be called only after begin_indexed()");
+        assert!(
+            (index as usize) < self.vertex_len,
+            "index ({}) out of range of the promised vertex_len ({})",
+            index,
+            self.vertex_len
+        );
+        assert!(self.index_data.len() / index_type.size() < self.index_len);
+
+        match index_type {
+            GPUIndexType::U16 => {
+                let index: u16 = index.try_into().unwrap();
+                self.index_data.extend_from_slice(&index.to_ne_bytes());
+            }
+            GPUIndexType::U32 => self.index_data.extend_from_slice(&index.to_ne_bytes()),
+        }
+    }
+}
+
+/// A retained batch of vertices (and optionally indices) that is
+/// recorded once with the same attribute API as
+/// [`crate::GPUImmediate`] and uploaded to its own VBO/VAO. It can
+/// then be drawn any number of times with any [`Shader`] that is
+/// compatible with the batch's [`GPUVertFormat`].
+///
+/// # Example
+///
+/// ```no_run
+/// # use egui_glfw::{GPUBatch, GPUPrimType, GPUVertCompType, GPUVertFetchMode, Shader};
+/// # fn example(shader: &Shader) {
+/// let mut batch = GPUBatch::new(GPUPrimType::Lines);
+/// let format = batch.get_cleared_vertex_format();
+/// let pos_attr = format.add_attribute(
+///     "v_pos\0".to_string(),
+///     GPUVertCompType::F32,
+///     3,
+///     GPUVertFetchMode::Float,
+/// );
+///
+/// batch.begin(2);
+/// batch.vertex_3f(pos_attr, 0.0, 0.0, 0.0);
+/// batch.vertex_3f(pos_attr, 1.0, 0.0, 0.0);
+/// batch.end();
+///
+/// // every frame
+/// batch.draw(shader);
+/// # }
+/// ```
+pub struct GPUBatch {
+    prim_type: GPUPrimType,
+    data: GPUBatchData,
+
+    /// Number of vertices uploaded to the GPU.
+    uploaded_vertex_len: usize,
+    /// Number of indices uploaded to the GPU.
+    uploaded_index_len: usize,
+
+    /// The shader program for which the attribute pointers of the
+    /// vao are setup.
+    vao_program: Option<gl::types::GLuint>,
+
+    vbo_id: gl::types::GLuint,
+    ibo_id: gl::types::GLuint,
+    vao_id: gl::types::GLuint,
+}
+
+impl GPUBatch {
+    /// Create a new empty [`GPUBatch`] of the given primitive type.
+    pub fn new(prim_type: GPUPrimType) -> Self {
+        assert_ne!(prim_type, GPUPrimType::None);
+
+        let mut vbo_id = 0;
+        let mut ibo_id = 0;
+        let mut vao_id = 0;
+        gpu_buf_alloc(&mut vbo_id);
+        gpu_buf_alloc(&mut ibo_id);
+        gpu_vao_alloc(&mut vao_id);
+
+        Self {
+            prim_type,
+            data: GPUBatchData::new(),
+
+            uploaded_vertex_len: 0,
+            uploaded_index_len: 0,
+
+            vao_program: None,
+
+            vbo_id,
+            ibo_id,
+            vao_id,
+        }
+    }
+
+    /// Get the cleared vertex format of the batch. Any previously
+    /// recorded data must be re-recorded after changing the format.
+    pub fn get_cleared_vertex_format(&mut self) -> &mut GPUVertFormat {
+        assert!(!self.data.recording);
+        self.data.vertex_format.clear();
+        self.uploaded_vertex_len = 0;
+        self.uploaded_index_len = 0;
+        self.vao_program = None;
+        &mut self.data.vertex_format
+    }
+
+    /// Begin recording `vertex_len` vertices, replacing any
+    /// previously recorded data.
+    pub fn begin(&mut self, vertex_len: usize) {
+        self.data.begin(vertex_len);
+    }
+
+    /// Begin recording `vertex_len` vertices along with `index_len`
+    /// indices (see [`Self::index()`]), replacing any previously
+    /// recorded data.
+    pub fn begin_indexed(&mut self, vertex_len: usize, index_type: GPUIndexType, index_len: usize) {
+        self.data.begin_indexed(vertex_len, index_type, index_len);
+    }
+
+    /// End recording and upload the recorded data to the GPU.
+    pub fn end(&mut self) {
+        self.data.finish();
+
+        unsafe {
+            gl::BindBuffer(gl::ARRAY_BUFFER, self.vbo_id);
+            gl::BufferData(
+                gl::ARRAY_BUFFER,
+                self.data.vertex_data.len().try_into().unwrap(),
+                self.data.vertex_data.as_ptr() as *const gl::types::GLvoid,
+                gl::STATIC_DRAW,
+            );
+            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+        }
+
+        if self.data.index_type.is_some() {
+            unsafe {
+                // the element array buffer binding is part of the vao
+                // state
+                gl::BindVertexArray(self.vao_id);
+                gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, self.ibo_id);
+                gl::BufferData(
+                    gl::ELEMENT_ARRAY_BUFFER,
+                    self.data.index_data.len().try_into().unwrap(),
+                    self.data.index_data.as_ptr() as *const gl::types::GLvoid,
+                    gl::STATIC_DRAW,
+                );
+                gl::BindVertexArray(0);
+            }
+        }
+
+        self.uploaded_vertex_len = self.data.vertex_len;
+        self.uploaded_index_len = self.data.index_len;
+        // attribute pointers must be setup again since the buffer
+        // storage was recreated
+        self.vao_program = None;
+
+        // the data now lives on the GPU
+        self.data.vertex_data = Vec::new();
+        self.data.index_data = Vec::new();
+    }
+
+    /// Draw the batch with the given shader. The shader is made
+    /// active.
+    ///
+    /// Attributes of the batch that are not used by the shader are
+    /// ignored.
+    pub fn draw(&mut self, shader: &Shader) {
+        assert!(
+            !self.data.recording,
+            "draw() called while recording the batch"
+        );
+
+        if self.uploaded_vertex_len == 0 {
+            return;
+        }
+
+        shader.use_shader();
+
+        unsafe {
+            gl::BindVertexArray(self.vao_id);
+        }
+
+        if self.vao_program != Some(shader.get_id()) {
+            self.attr_pointers_setup(shader);
+            self.vao_program = Some(shader.get_id());
+        }
+
+        #[cfg(target_os = "macos")]
+        unsafe {
+            gl::Disable(gl::PRIMITIVE_RESTART);
+        }
+
+        match self.data.index_type {
+            Some(index_type) => unsafe {
+                gl::DrawElements(
+                    self.prim_type.to_gl(),
+                    self.uploaded_index_len.try_into().unwrap(),
+                    index_type.to_gl(),
+                    std::ptr::null(),
+                );
+            },
+            None => unsafe {
+                gl::DrawArrays(
+                    self.prim_type.to_gl(),
+                    0,
+                    self.uploaded_vertex_len.try_into().unwrap(),
+                );
+            },
+        }
+
+        #[cfg(target_os = "macos")]
+        unsafe {
+            gl::Enable(gl::PRIMITIVE_RESTART);
+        }
+
+        unsafe {
+            gl::BindVertexArray(0);
+        }
+    }
+
+    /// Setup the attribute pointers of the vao for the given shader,
+    /// the vao must be bound.
+    fn attr_pointers_setup(&self, shader: &Shader) {
+        unsafe {
+            gl::BindBuffer(gl::ARRAY_BUFFER, self.vbo_id);
+
+            // a previous shader may have used different locations
+            (0..GPU_VERT_ATTR_MAX_LEN)
+                .for_each(|loc| gl::DisableVertexAttribArray(loc.try_into().unwrap()));
+        }
+
+        let format = &self.data.vertex_format;
+        for attr_index in 0..format.get_attrs_len() {
+            let location = unsafe {
+                gl::GetAttribLocation(
+                    shader.get_id(),
+                    str_to_cstr(format.get_attr_name(attr_index)).as_ptr(),
+                )
+            };
+
+            // attribute is not used by the shader
+            if location < 0 {
+                continue;
+            }
+            let location = location.try_into().unwrap();
+
+            unsafe {
+                gl::EnableVertexAttribArray(location);
+            }
+            format.attr_pointer_setup(attr_index, location, 0, 0);
+        }
+
+        unsafe {
+            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+        }
+    }
+
+    pub fn attr_1f(&mut self, attr_id: usize, x: f32) {
+        self.data.set_attr_value(attr_id, &[x]);
+    }
+
+    pub fn attr_2f(&mut self, attr_id: usize, x: f32, y: f32) {
+        self.data.set_attr_value(attr_id, &[x, y]);
+    }
+
+    pub fn attr_3f(&mut self, attr_id: usize, x: f32, y: f32, z: f32) {
+        self.data.set_attr_value(attr_id, &[x, y, z]);
+    }
+
+    pub fn attr_4f(&mut self, attr_id: usize, x: f32, y: f32, z: f32, w: f32) {
+        self.data.set_attr_value(attr_id, &[x, y, z, w]);
+    }
+
+    pub fn vertex_2f(&mut self, attr_id: usize, x: f32, y: f32) {
+        self.attr_2f(attr_id, x, y);
+        self.data.end_vertex();
+    }
+
+    pub fn vertex_3f(&mut self, attr_id: usize, x: f32, y: f32, z: f32) {
+        self.attr_3f(attr_id, x, y, z);
+        self.data.end_vertex();
+    }
+
+    pub fn vertex_4f(&mut self, attr_id: usize, x: f32, y: f32, z: f32, w: f32) {
+        self.attr_4f(attr_id, x, y, z, w);
+        self.data.end_vertex();
+    }
+
+    /// Add an index to the batch, see [`Self::begin_indexed()`].
+    pub fn index(&mut self, index: u32) {
+        self.data.index(index);
+    }
+}
+
+impl Drop for GPUBatch {
+    fn drop(&mut self) {
+        gpu_buf_free(&self.vbo_id);
+        gpu_buf_free(&self.ibo_id);
+        gpu_vao_free(&self.vao_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::gpu_immediate::{GPUVertCompType, GPUVertFetchMode};
+
+    /// Get the bytes of the `f32` `values`.
+    fn f32_bytes(values: &[f32]) -> Vec<u8> {
+        values
+            .iter()
+            .flat_map(|value| value.to_ne_bytes())
+            .collect()
+    }
+
+    /// Get a [`GPUBatchData`] with a vec2 position and a vec4 color.
+    fn pos_color_data() -> (GPUBatchData, usize, usize) {
+        let mut data = GPUBatchData::new();
+        let pos = data.vertex_format.add_attribute(
+            "pos\0".to_string(),
+            GPUVertCompType::F32,
+            2,
+            GPUVertFetchMode::Float,
+        );
+        let color = data.vertex_format.add_attribute(
+            "color\0".to_string(),
+            GPUVertCompType::F32,
+            4,
+            GPUVertFetchMode::Float,
+        );
+        (data, pos, color)
+    }
+
+    #[test]
+    fn vertices_take_unassigned_attrs_from_previous() {
+        let (mut data, pos, color) = pos_color_data();
+
+        data.begin(2);
+        data.set_attr_value(color, &[1.0, 0.5, 0.25, 1.0]);
+        data.set_attr_value(pos, &[0.0, 0.0]);
+        data.end_vertex();
+        data.set_attr_value(pos, &[2.0, 3.0]);
+        data.end_vertex();
+        data.finish();
+
+        let expected = [
+            f32_bytes(&[0.0, 0.0, 1.0, 0.5, 0.25, 1.0]),
+            f32_bytes(&[2.0, 3.0, 1.0, 0.5, 0.25, 1.0]),
+        ]
+        .concat();
+        assert_eq!(data.vertex_data, expected);
+    }
+
+    #[test]
+    fn indices_are_encoded_with_index_type() {
+        let (mut data, pos, _) = pos_color_data();
+
+        data.begin_indexed(3, GPUIndexType::U16, 3);
+        (0..3).for_each(|i| {
+            data.set_attr_value(pos, &[i as f32, 0.0]);
+            data.set_attr_value(1, &[1.0, 1.0, 1.0, 1.0]);
+            data.end_vertex();
+        });
+        [2, 0, 1].iter().for_each(|index| data.index(*index));
+        data.finish();
+
+        let expected: Vec<u8> = [2_u16, 0, 1]
+            .iter()
+            .flat_map(|index| index.to_ne_bytes())
+            .collect();
+        assert_eq!(data.index_data, expected);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range of the promised vertex_len")]
+    fn index_out_of_range() {
+        let (mut data, _, _) = pos_color_data();
+        data.begin_indexed(2, GPUIndexType::U32, 1);
+        data.index(2);
+    }
+
+    #[test]
+    #[should_panic(expected = "number of verts is not equal to promised vertex_len")]
+    fn missing_vertices() {
+        let (mut data, pos, color) = pos_color_data();
+        data.begin(2);
+        data.set_attr_value(pos, &[0.0, 0.0]);
+        data.set_attr_value(color, &[1.0, 1.0, 1.0, 1.0]);
+        data.end_vertex();
+        data.finish();
+    }
+
+    #[test]
+    #[should_panic(expected = "is not F32")]
+    fn integer_attr_rejects_f32_values() {
+        let mut data = GPUBatchData::new();
+        let color = data.vertex_format.add_attribute(
+            "color\0".to_string(),
+            GPUVertCompType::U8,
+            4,
+            GPUVertFetchMode::IntToFloatUnit,
+        );
+        data.begin(1);
+        data.set_attr_value(color, &[1.0]);
+    }
+}
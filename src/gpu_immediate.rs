@@ -1,13 +1,13 @@
 // Based on Blender's GPU Immediate work-alike system
-#![allow(dead_code)]
 use std::convert::TryInto;
 
 use crate::shader::Shader;
 use crate::util::str_to_cstr;
 
-const GPU_VERT_ATTR_MAX_LEN: usize = 16;
+pub const GPU_VERT_ATTR_MAX_LEN: usize = 16;
 const IMM_DEFAULT_BUFFER_SIZE: usize = 4 * 1024 * 1024;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum GPUVertCompType {
     I8,
     U8,
@@ -21,7 +21,7 @@ pub enum GPUVertCompType {
 }
 
 impl GPUVertCompType {
-    fn to_gl(&self) -> gl::types::GLenum {
+    fn to_gl(self) -> gl::types::GLenum {
         match self {
             GPUVertCompType::I8 => gl::BYTE,
             GPUVertCompType::U8 => gl::UNSIGNED_BYTE,
@@ -36,6 +36,7 @@ impl GPUVertCompType {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum GPUVertFetchMode {
     Float,
     Int,
@@ -44,6 +45,35 @@ pub enum GPUVertFetchMode {
     None,
 }
 
+/// Type of the indices in the index stream of
+/// [`GPUImmediate::begin_indexed()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GPUIndexType {
+    U16,
+    U32,
+}
+
+impl GPUIndexType {
+    pub fn to_gl(self) -> gl::types::GLenum {
+        match self {
+            GPUIndexType::U16 => gl::UNSIGNED_SHORT,
+            GPUIndexType::U32 => gl::UNSIGNED_INT,
+        }
+    }
+
+    pub fn size(self) -> usize {
+        match self {
+            GPUIndexType::U16 => 2,
+            GPUIndexType::U32 => 4,
+        }
+    }
+
+    /// Get the size (in bytes) of `index_len` indices.
+    pub fn buffer_size(self, index_len: usize) -> usize {
+        index_len * self.size()
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub enum GPUPrimType {
     Points,
@@ -62,7 +92,7 @@ pub enum GPUPrimType {
 }
 
 impl GPUPrimType {
-    fn to_gl(&self) -> gl::types::GLenum {
+    pub fn to_gl(&self) -> gl::types::GLenum {
         match self {
             GPUPrimType::Points => gl::POINTS,
             GPUPrimType::Lines => gl::LINES,
@@ -173,6 +203,8 @@ impl GPUVertAttr {
     }
 }
 
+/// Format of the vertices (or instances) given to
+/// [`GPUImmediate`].
 pub struct GPUVertFormat {
     stride: u16,  // stride in bytes 1 to 1024
     packed: bool, // has the format been packed
@@ -189,8 +221,14 @@ fn padding(offset: usize, alignment: usize) -> usize {
     }
 }
 
+impl Default for GPUVertFormat {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl GPUVertFormat {
-    fn new() -> Self {
+    pub fn new() -> Self {
         GPUVertFormat {
             stride: 0,
             packed: false,
@@ -226,7 +264,7 @@ impl GPUVertFormat {
         self.packed = true;
     }
 
-    fn vertex_buffer_size(&self, vertex_len: usize) -> usize {
+    pub fn vertex_buffer_size(&self, vertex_len: usize) -> usize {
         self.stride as usize * vertex_len
     }
 
@@ -259,6 +297,109 @@ impl GPUVertFormat {
         // vertex shader. Need to figure out what is happening
     }
 
+    /// Pack the format if it is not packed already.
+    pub fn ensure_packed(&mut self) {
+        if !self.packed {
+            self.pack();
+        }
+    }
+
+    /// Get the stride (in bytes) of a vertex, the format must be
+    /// packed.
+    pub fn get_stride(&self) -> usize {
+        assert!(self.packed);
+        self.stride.into()
+    }
+
+    /// Get the number of attributes in the format.
+    pub fn get_attrs_len(&self) -> usize {
+        self.attrs.len()
+    }
+
+    /// Get the name of the attribute (with the trailing `\0`).
+    pub fn get_attr_name(&self, attr_index: usize) -> &str {
+        &self.attrs[attr_index].name
+    }
+
+    /// Get the offset and size (in bytes) of the attribute within a
+    /// vertex, the format must be packed.
+    pub fn get_attr_offset_and_size(&self, attr_index: usize) -> (usize, usize) {
+        assert!(self.packed);
+        let attr = &self.attrs[attr_index];
+        (attr.offset.into(), attr.sz.into())
+    }
+
+    /// Setup the attribute pointer of the attribute at `attr_index`
+    /// at location `loc` for the buffer currently bound to
+    /// `gl::ARRAY_BUFFER` starting at `buffer_offset`. `divisor` is 0
+    /// for per vertex attributes and 1 for per instance attributes.
+    pub fn attr_pointer_setup(
+        &self,
+        attr_index: usize,
+        loc: gl::types::GLuint,
+        buffer_offset: usize,
+        divisor: gl::types::GLuint,
+    ) {
+        let stride = self.stride;
+        let a = &self.attrs[attr_index];
+
+        let offset = buffer_offset + a.offset as usize;
+        // let pointer: *mut gl::types::GLubyte = offset as *mut gl::types::GLubyte;
+        let pointer: *mut gl::types::GLubyte;
+        unsafe {
+            let null: *mut gl::types::GLubyte = std::ptr::null_mut();
+            pointer = null.offset(offset.try_into().unwrap());
+        }
+
+        unsafe {
+            gl::VertexAttribDivisor(loc, divisor);
+        }
+
+        match a.fetch_mode {
+            GPUVertFetchMode::Float | GPUVertFetchMode::IntToFloat => unsafe {
+                gl::VertexAttribPointer(
+                    loc,
+                    a.comp_len.into(),
+                    a.gl_comp_type,
+                    gl::FALSE,
+                    stride.into(),
+                    pointer as *const gl::types::GLvoid,
+                );
+            },
+            GPUVertFetchMode::IntToFloatUnit => unsafe {
+                gl::VertexAttribPointer(
+                    loc,
+                    a.comp_len.into(),
+                    a.gl_comp_type,
+                    gl::TRUE,
+                    stride.into(),
+                    pointer as *const gl::types::GLvoid,
+                );
+            },
+            GPUVertFetchMode::Int => unsafe {
+                gl::VertexAttribIPointer(
+                    loc,
+                    a.comp_len.into(),
+                    a.gl_comp_type,
+                    stride.into(),
+                    pointer as *const gl::types::GLvoid,
+                );
+            },
+            GPUVertFetchMode::None => panic!("GPUVertFetchMode shouldn't be None"),
+        }
+    }
+
+    /// Set the `f32` `values` of the attribute at `attr_index` in
+    /// `vertex`, a vertex of this format. Only
+    /// [`GPUVertCompType::F32`] attributes can be given `f32` values.
+    pub(crate) fn set_attr_f32(&self, vertex: &mut [u8], attr_index: usize, values: &[f32]) {
+        assert_eq!(vertex.len(), self.get_stride());
+        // SAFETY: `vertex` is a whole vertex of the format
+        unsafe {
+            write_attr_f32(vertex.as_mut_ptr(), &self.attrs[attr_index], values);
+        }
+    }
+
     pub fn clear(&mut self) {
         self.attrs.clear();
         self.packed = false;
@@ -266,6 +407,13 @@ impl GPUVertFormat {
     }
 }
 
+/// Immediate mode drawing: the vertices (along with the indices
+/// and instances) of a draw are streamed to the GPU between
+/// [`Self::begin()`] and [`Self::end()`], useful for custom widgets
+/// and debug overlays drawn along with egui.
+///
+/// An OpenGL context must be current and the symbols loaded (see
+/// [`crate::EguiBackend::new()`]).
 pub struct GPUImmediate {
     buffer_data: *mut gl::types::GLubyte,
     buffer_offset: usize,
@@ -285,32 +433,214 @@ pub struct GPUImmediate {
 
     attr_binding: GPUAttrBinding,
     prev_enabled_attr_bits: u16, // affects only this vao
+
+    index_type: Option<GPUIndexType>,
+    index_len: usize,
+    index_idx: usize,
+    index_data: *mut gl::types::GLubyte,
+    index_buffer: GPUStreamBuffer,
+
+    instance_len: Option<usize>,
+    instance_format: GPUVertFormat,
+    instance_idx: usize,
+    instance_data: *mut gl::types::GLubyte,
+    unassigned_instance_attr_bits: u16, // which attributes of the current instance have not been given values
+    instance_attr_binding: GPUAttrBinding,
+    instance_buffer: GPUStreamBuffer,
 }
 
-fn gpu_buf_alloc(id: &mut gl::types::GLuint) {
+pub fn gpu_buf_alloc(id: &mut gl::types::GLuint) {
     unsafe {
         gl::GenBuffers(1, id);
     }
 }
 
-fn gpu_buf_free(id: &gl::types::GLuint) {
+pub fn gpu_buf_free(id: &gl::types::GLuint) {
     unsafe {
         gl::DeleteBuffers(1, id);
     }
 }
 
-fn gpu_vao_alloc(id: &mut gl::types::GLuint) {
+pub fn gpu_vao_alloc(id: &mut gl::types::GLuint) {
     unsafe {
         gl::GenVertexArrays(1, id);
     }
 }
 
-fn gpu_vao_free(id: &gl::types::GLuint) {
+pub fn gpu_vao_free(id: &gl::types::GLuint) {
     unsafe {
         gl::DeleteVertexArrays(1, id);
     }
 }
 
+/// Write the `f32` `values` to the attribute `attr` of the vertex
+/// (or instance) at `data`. Only [`GPUVertCompType::F32`] attributes
+/// can be given `f32` values.
+///
+/// # Safety
+///
+/// `data` must point to a vertex of the format of `attr`.
+unsafe fn write_attr_f32(data: *mut gl::types::GLubyte, attr: &GPUVertAttr, values: &[f32]) {
+    assert_eq!(
+        attr.comp_type,
+        GPUVertCompType::F32,
+        "attribute {} is not F32, it cannot be given f32 values",
+        attr.name.trim_end_matches('\0')
+    );
+    assert!(
+        values.len() <= attr.comp_len.into(),
+        "attribute {} has {} components, given {} values",
+        attr.name.trim_end_matches('\0'),
+        attr.comp_len,
+        values.len()
+    );
+
+    let data = data.offset(attr.offset.into()) as *mut f32;
+    values
+        .iter()
+        .enumerate()
+        .for_each(|(i, value)| data.add(i).write_unaligned(*value));
+}
+
+/// Range of a [`GPUStreamBuffer`] to map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct GPUStreamRange {
+    /// Size of the buffer (in bytes).
+    size: usize,
+    /// Offset of the range (in bytes).
+    offset: usize,
+    /// Should the storage of the buffer be reallocated, orphaning the
+    /// previous storage.
+    orphan: bool,
+}
+
+impl GPUStreamRange {
+    /// Get the range of `bytes_needed` bytes aligned to `alignment`
+    /// within a buffer of `size` bytes that is used up to `offset`.
+    fn new(size: usize, offset: usize, bytes_needed: usize, alignment: usize) -> Self {
+        let (size, resize) = if bytes_needed > size {
+            // expand buffer
+            (bytes_needed.max(IMM_DEFAULT_BUFFER_SIZE), true)
+        } else if bytes_needed < IMM_DEFAULT_BUFFER_SIZE && size > IMM_DEFAULT_BUFFER_SIZE {
+            // shrink buffer
+            (IMM_DEFAULT_BUFFER_SIZE, true)
+        } else {
+            // no change to size of buffer
+            (size, false)
+        };
+
+        let pre_padding = padding(offset, alignment);
+
+        if !resize && (offset + pre_padding + bytes_needed) <= size {
+            Self {
+                size,
+                offset: offset + pre_padding,
+                orphan: false,
+            }
+        } else {
+            Self {
+                size,
+                offset: 0,
+                orphan: true,
+            }
+        }
+    }
+}
+
+/// A streamed buffer that is orphaned when it runs out of space,
+/// used for the index and per instance data of [`GPUImmediate`]. It
+/// follows the same strategy as the vertex buffer of
+/// [`GPUImmediate`].
+struct GPUStreamBuffer {
+    id: gl::types::GLuint,
+    target: gl::types::GLenum,
+    size: usize,
+    offset: usize,
+    bytes_mapped: usize,
+}
+
+impl GPUStreamBuffer {
+    /// Create a new [`GPUStreamBuffer`]. The storage of the buffer
+    /// is allocated on the first [`Self::map()`] since
+    /// `gl::ELEMENT_ARRAY_BUFFER` can only be bound while a vao is
+    /// bound.
+    fn new(target: gl::types::GLenum) -> Self {
+        let mut id = 0;
+        gpu_buf_alloc(&mut id);
+
+        Self {
+            id,
+            target,
+            size: 0,
+            offset: 0,
+            bytes_mapped: 0,
+        }
+    }
+
+    /// Map `bytes_needed` bytes of the buffer for writing. The start
+    /// of the mapped range is aligned to `alignment`.
+    ///
+    /// The buffer is left bound to its target.
+    fn map(&mut self, bytes_needed: usize, alignment: usize) -> *mut gl::types::GLubyte {
+        assert_ne!(bytes_needed, 0);
+
+        unsafe {
+            gl::BindBuffer(self.target, self.id);
+        }
+
+        let range = GPUStreamRange::new(self.size, self.offset, bytes_needed, alignment);
+        self.size = range.size;
+        self.offset = range.offset;
+
+        if range.orphan {
+            // create a new buffer
+            unsafe {
+                gl::BufferData(
+                    self.target,
+                    self.size.try_into().unwrap(),
+                    std::ptr::null(),
+                    gl::DYNAMIC_DRAW,
+                );
+            }
+        }
+
+        let data = unsafe {
+            gl::MapBufferRange(
+                self.target,
+                self.offset.try_into().unwrap(),
+                bytes_needed.try_into().unwrap(),
+                gl::MAP_WRITE_BIT | gl::MAP_UNSYNCHRONIZED_BIT,
+            ) as *mut gl::types::GLubyte
+        };
+        assert_ne!(data, std::ptr::null_mut());
+
+        self.bytes_mapped = bytes_needed;
+
+        data
+    }
+
+    /// Unmap the buffer, the buffer is left bound to its target.
+    fn unmap(&mut self) {
+        unsafe {
+            gl::BindBuffer(self.target, self.id);
+            gl::UnmapBuffer(self.target);
+        }
+    }
+
+    /// Mark the currently mapped range as used so the next
+    /// [`Self::map()`] does not overwrite it.
+    fn advance(&mut self) {
+        self.offset += self.bytes_mapped;
+        self.bytes_mapped = 0;
+    }
+}
+
+impl Drop for GPUStreamBuffer {
+    fn drop(&mut self) {
+        gpu_buf_free(&self.id);
+    }
+}
+
 impl Default for GPUImmediate {
     fn default() -> Self {
         Self::new()
@@ -338,6 +668,20 @@ impl GPUImmediate {
 
             attr_binding: GPUAttrBinding::new(),
             prev_enabled_attr_bits: 0,
+
+            index_type: None,
+            index_len: 0,
+            index_idx: 0,
+            index_data: std::ptr::null_mut(),
+            index_buffer: GPUStreamBuffer::new(gl::ELEMENT_ARRAY_BUFFER),
+
+            instance_len: None,
+            instance_format: GPUVertFormat::new(),
+            instance_idx: 0,
+            instance_data: std::ptr::null_mut(),
+            unassigned_instance_attr_bits: 0,
+            instance_attr_binding: GPUAttrBinding::new(),
+            instance_buffer: GPUStreamBuffer::new(gl::ARRAY_BUFFER),
         };
 
         imm.init();
@@ -490,9 +834,112 @@ impl GPUImmediate {
         self.begin(prim_type, vertex_len, shader);
     }
 
+    /// Begin an indexed draw. `vertex_len` vertices must be provided
+    /// along with `index_len` indices (see [`Self::index()`]) that
+    /// refer to the vertices of this draw.
+    pub fn begin_indexed(
+        &mut self,
+        prim_type: GPUPrimType,
+        vertex_len: usize,
+        index_type: GPUIndexType,
+        index_len: usize,
+        shader: &Shader,
+    ) {
+        assert_ne!(index_len, 0);
+
+        self.begin(prim_type, vertex_len, shader);
+
+        // the element array buffer binding is part of the vao state
+        unsafe {
+            gl::BindVertexArray(self.vao_id);
+        }
+
+        self.index_type = Some(index_type);
+        self.index_len = index_len;
+        self.index_idx = 0;
+        self.index_data = self
+            .index_buffer
+            .map(index_type.buffer_size(index_len), index_type.size());
+    }
+
+    /// Begin an instanced draw. `vertex_len` vertices must be
+    /// provided which are drawn `instance_len` times. The per
+    /// instance attributes (see [`Self::get_cleared_instance_format()`])
+    /// must be provided for each instance (see
+    /// [`Self::end_instance()`]).
+    pub fn begin_instanced(
+        &mut self,
+        prim_type: GPUPrimType,
+        vertex_len: usize,
+        instance_len: usize,
+        shader: &Shader,
+    ) {
+        assert_ne!(instance_len, 0);
+
+        self.begin(prim_type, vertex_len, shader);
+        self.setup_instances(instance_len, shader);
+    }
+
+    /// Begin an indexed and instanced draw, see
+    /// [`Self::begin_indexed()`] and [`Self::begin_instanced()`].
+    pub fn begin_indexed_instanced(
+        &mut self,
+        prim_type: GPUPrimType,
+        vertex_len: usize,
+        index_type: GPUIndexType,
+        index_len: usize,
+        instance_len: usize,
+        shader: &Shader,
+    ) {
+        assert_ne!(instance_len, 0);
+
+        self.begin_indexed(prim_type, vertex_len, index_type, index_len, shader);
+        self.setup_instances(instance_len, shader);
+    }
+
+    fn setup_instances(&mut self, instance_len: usize, shader: &Shader) {
+        self.instance_attr_binding.clear();
+        self.instance_len = Some(instance_len);
+        self.instance_idx = 0;
+
+        // instances need not have any attributes, the shader can make
+        // use of `gl_InstanceID` instead
+        if self.instance_format.attrs.is_empty() {
+            self.unassigned_instance_attr_bits = 0;
+            return;
+        }
+
+        if !self.instance_format.packed {
+            self.instance_format.pack();
+        }
+
+        for (attr_index, a) in self.instance_format.attrs.iter().enumerate() {
+            let location;
+            unsafe {
+                location = gl::GetAttribLocation(shader.get_id(), str_to_cstr(&a.name).as_ptr());
+            }
+            self.instance_attr_binding
+                .write_attr_location(attr_index, location.try_into().unwrap());
+        }
+
+        self.unassigned_instance_attr_bits = self.instance_attr_binding.enabled_bits;
+
+        let stride: usize = self.instance_format.stride.into();
+        self.instance_data = self.instance_buffer.map(
+            self.instance_format.vertex_buffer_size(instance_len),
+            stride,
+        );
+    }
+
     pub fn end(&mut self) {
         assert_ne!(self.prim_type, GPUPrimType::None); // to ensure end isn't called before begin
 
+        // the index and instance buffers may have been bound to
+        // `gl::ARRAY_BUFFER` after the vertex buffer was mapped
+        unsafe {
+            gl::BindBuffer(gl::ARRAY_BUFFER, self.vbo_id);
+        }
+
         let buffer_bytes_used;
         if self.strict_vertex_len {
             assert_eq!(self.vertex_idx, self.vertex_len, "number of verts is not equal to promised vertex_len; self.vertex_idx: {}, self.vertex_len: {}", self.vertex_idx, self.vertex_len);
@@ -520,6 +967,21 @@ impl GPUImmediate {
             gl::UnmapBuffer(gl::ARRAY_BUFFER);
         }
 
+        if self.index_type.is_some() {
+            assert_eq!(self.index_idx, self.index_len, "number of indices is not equal to promised index_len; self.index_idx: {}, self.index_len: {}", self.index_idx, self.index_len);
+            unsafe {
+                gl::BindVertexArray(self.vao_id);
+            }
+            self.index_buffer.unmap();
+        }
+
+        if let Some(instance_len) = self.instance_len {
+            assert_eq!(self.instance_idx, instance_len, "number of instances is not equal to promised instance_len; self.instance_idx: {}, instance_len: {}", self.instance_idx, instance_len);
+            if !self.instance_format.attrs.is_empty() {
+                self.instance_buffer.unmap();
+            }
+        }
+
         if self.vertex_len > 0 {
             self.draw_setup();
 
@@ -528,12 +990,39 @@ impl GPUImmediate {
                 gl::Disable(gl::PRIMITIVE_RESTART);
             }
 
-            unsafe {
-                gl::DrawArrays(
-                    self.prim_type.to_gl(),
-                    0,
-                    self.vertex_len.try_into().unwrap(),
-                );
+            match (self.index_type, self.instance_len) {
+                (None, None) => unsafe {
+                    gl::DrawArrays(
+                        self.prim_type.to_gl(),
+                        0,
+                        self.vertex_len.try_into().unwrap(),
+                    );
+                },
+                (None, Some(instance_len)) => unsafe {
+                    gl::DrawArraysInstanced(
+                        self.prim_type.to_gl(),
+                        0,
+                        self.vertex_len.try_into().unwrap(),
+                        instance_len.try_into().unwrap(),
+                    );
+                },
+                (Some(index_type), None) => unsafe {
+                    gl::DrawElements(
+                        self.prim_type.to_gl(),
+                        self.index_len.try_into().unwrap(),
+                        index_type.to_gl(),
+                        self.index_buffer.offset as *const gl::types::GLvoid,
+                    );
+                },
+                (Some(index_type), Some(instance_len)) => unsafe {
+                    gl::DrawElementsInstanced(
+                        self.prim_type.to_gl(),
+                        self.index_len.try_into().unwrap(),
+                        index_type.to_gl(),
+                        self.index_buffer.offset as *const gl::types::GLvoid,
+                        instance_len.try_into().unwrap(),
+                    );
+                },
             }
 
             #[cfg(target_os = "macos")]
@@ -542,11 +1031,38 @@ impl GPUImmediate {
             }
 
             self.buffer_offset += buffer_bytes_used;
+            if self.index_type.is_some() {
+                self.index_buffer.advance();
+            }
+            if self.instance_len.is_some() && !self.instance_format.attrs.is_empty() {
+                self.instance_buffer.advance();
+            }
         }
 
         // setup for next begin
         self.prim_type = GPUPrimType::None;
         self.strict_vertex_len = true;
+        self.index_type = None;
+        self.instance_len = None;
+    }
+
+    /// Get the locations of all the attributes enabled for the
+    /// current draw as a bit mask.
+    fn enabled_attr_locations(&self) -> u16 {
+        let vertex_locations = (0..self.vertex_format.attrs.len())
+            .map(|attr_index| self.attr_binding.read_attr_location(attr_index));
+        let instance_locations = self
+            .instance_len
+            .map(|_| {
+                (0..self.instance_format.attrs.len())
+                    .map(|attr_index| self.instance_attr_binding.read_attr_location(attr_index))
+            })
+            .into_iter()
+            .flatten();
+
+        vertex_locations
+            .chain(instance_locations)
+            .fold(0, |bits, loc| bits | (1 << loc))
     }
 
     fn draw_setup(&mut self) {
@@ -554,9 +1070,10 @@ impl GPUImmediate {
             gl::BindVertexArray(self.vao_id);
         }
 
-        if self.attr_binding.enabled_bits != self.prev_enabled_attr_bits {
+        let enabled_attr_locations = self.enabled_attr_locations();
+        if enabled_attr_locations != self.prev_enabled_attr_bits {
             for loc in 0..GPU_VERT_ATTR_MAX_LEN {
-                let is_enabled: bool = (self.attr_binding.enabled_bits & (1 << loc)) != 0;
+                let is_enabled: bool = (enabled_attr_locations & (1 << loc)) != 0;
                 let was_enabled: bool = (self.prev_enabled_attr_bits & (1 << loc)) != 0;
 
                 if is_enabled && !was_enabled {
@@ -570,54 +1087,51 @@ impl GPUImmediate {
                 }
             }
 
-            self.prev_enabled_attr_bits = self.attr_binding.enabled_bits;
+            self.prev_enabled_attr_bits = enabled_attr_locations;
         }
 
-        let stride = self.vertex_format.stride;
-
-        for (attr_index, a) in self.vertex_format.attrs.iter().enumerate() {
-            let offset = self.buffer_offset + a.offset as usize;
-            // let pointer: *mut gl::types::GLubyte = offset as *mut gl::types::GLubyte;
-            let pointer: *mut gl::types::GLubyte;
+        unsafe {
+            gl::BindBuffer(gl::ARRAY_BUFFER, self.vbo_id);
+        }
+        Self::attr_pointers_setup(
+            &self.vertex_format,
+            &self.attr_binding,
+            self.buffer_offset,
+            0,
+        );
+
+        if self.index_type.is_some() {
             unsafe {
-                let null: *mut gl::types::GLubyte = std::ptr::null_mut();
-                pointer = null.offset(offset.try_into().unwrap());
+                gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, self.index_buffer.id);
             }
+        }
 
-            let loc = self.attr_binding.read_attr_location(attr_index);
-
-            match a.fetch_mode {
-                GPUVertFetchMode::Float | GPUVertFetchMode::IntToFloat => unsafe {
-                    gl::VertexAttribPointer(
-                        loc.try_into().unwrap(),
-                        a.comp_len.into(),
-                        a.gl_comp_type,
-                        gl::FALSE,
-                        stride.into(),
-                        pointer as *const gl::types::GLvoid,
-                    );
-                },
-                GPUVertFetchMode::IntToFloatUnit => unsafe {
-                    gl::VertexAttribPointer(
-                        loc.try_into().unwrap(),
-                        a.comp_len.into(),
-                        a.gl_comp_type,
-                        gl::TRUE,
-                        stride.into(),
-                        pointer as *const gl::types::GLvoid,
-                    );
-                },
-                GPUVertFetchMode::Int => unsafe {
-                    gl::VertexAttribIPointer(
-                        loc.try_into().unwrap(),
-                        a.comp_len.into(),
-                        a.gl_comp_type,
-                        stride.into(),
-                        pointer as *const gl::types::GLvoid,
-                    );
-                },
-                GPUVertFetchMode::None => panic!("GPUVertFetchMode shouldn't be None"),
+        if self.instance_len.is_some() && !self.instance_format.attrs.is_empty() {
+            unsafe {
+                gl::BindBuffer(gl::ARRAY_BUFFER, self.instance_buffer.id);
             }
+            Self::attr_pointers_setup(
+                &self.instance_format,
+                &self.instance_attr_binding,
+                self.instance_buffer.offset,
+                1,
+            );
+        }
+    }
+
+    /// Setup the attribute pointers of the `format` for the buffer
+    /// currently bound to `gl::ARRAY_BUFFER` starting at
+    /// `buffer_offset`. `divisor` is 0 for per vertex attributes and
+    /// 1 for per instance attributes.
+    fn attr_pointers_setup(
+        format: &GPUVertFormat,
+        attr_binding: &GPUAttrBinding,
+        buffer_offset: usize,
+        divisor: gl::types::GLuint,
+    ) {
+        for attr_index in 0..format.attrs.len() {
+            let loc = attr_binding.read_attr_location(attr_index);
+            format.attr_pointer_setup(attr_index, loc.try_into().unwrap(), buffer_offset, divisor);
         }
     }
 
@@ -626,55 +1140,42 @@ impl GPUImmediate {
         &mut self.vertex_format
     }
 
+    /// Get the cleared format of the per instance attributes used by
+    /// [`Self::begin_instanced()`].
+    pub fn get_cleared_instance_format(&mut self) -> &mut GPUVertFormat {
+        self.instance_format.clear();
+        &mut self.instance_format
+    }
+
     fn set_attr_value_bit(&mut self, attr_id: usize) {
         let mask = 1 << attr_id;
         self.unassigned_attr_bits &= !mask;
     }
 
-    pub fn attr_1f(&mut self, attr_id: usize, x: f32) {
-        // TODO(ish): add asserts
+    fn set_attr_value(&mut self, attr_id: usize, values: &[f32]) {
+        assert_ne!(self.prim_type, GPUPrimType::None);
         self.set_attr_value_bit(attr_id);
         let attr = &self.vertex_format.attrs[attr_id];
+        // SAFETY: the vertex data points to the current vertex
         unsafe {
-            let data = self.vertex_data.offset(attr.offset.into()) as *mut f32;
-            *data = x;
+            write_attr_f32(self.vertex_data, attr, values);
         }
     }
 
+    pub fn attr_1f(&mut self, attr_id: usize, x: f32) {
+        self.set_attr_value(attr_id, &[x]);
+    }
+
     pub fn attr_2f(&mut self, attr_id: usize, x: f32, y: f32) {
-        // TODO(ish): add asserts
-        self.set_attr_value_bit(attr_id);
-        let attr = &self.vertex_format.attrs[attr_id];
-        unsafe {
-            let data = self.vertex_data.offset(attr.offset.into()) as *mut f32;
-            *data = x;
-            *data.offset(1) = y;
-        }
+        self.set_attr_value(attr_id, &[x, y]);
     }
 
     pub fn attr_3f(&mut self, attr_id: usize, x: f32, y: f32, z: f32) {
-        // TODO(ish): add asserts
-        self.set_attr_value_bit(attr_id);
-        let attr = &self.vertex_format.attrs[attr_id];
-        unsafe {
-            let data = self.vertex_data.offset(attr.offset.into()) as *mut f32;
-            *data = x;
-            *data.offset(1) = y;
-            *data.offset(2) = z;
-        }
+        self.set_attr_value(attr_id, &[x, y, z]);
     }
 
     pub fn attr_4f(&mut self, attr_id: usize, x: f32, y: f32, z: f32, w: f32) {
-        // TODO(ish): add asserts
-        self.set_attr_value_bit(attr_id);
-        let attr = &self.vertex_format.attrs[attr_id];
-        unsafe {
-            let data = self.vertex_data.offset(attr.offset.into()) as *mut f32;
-            *data = x;
-            *data.offset(1) = y;
-            *data.offset(2) = z;
-            *data.offset(3) = w;
-        }
+        self.set_attr_value(attr_id, &[x, y, z, w]);
     }
 
     fn end_vertex(&mut self) {
@@ -704,6 +1205,94 @@ impl GPUImmediate {
         self.unassigned_attr_bits = self.attr_binding.enabled_bits;
     }
 
+    /// Add an index to the index stream of the current indexed draw,
+    /// see [`Self::begin_indexed()`].
+    pub fn index(&mut self, index: u32) {
+        let index_type = self
+            .index_type
+            .expect("index() must be called only after begin_indexed()");
+        assert!(
+            (index as usize) < self.vertex_len,
+            "index ({}) out of range of the promised vertex_len ({})",
+            index,
+            self.vertex_len
+        );
+        assert!(self.index_idx < self.index_len);
+
+        unsafe {
+            match index_type {
+                GPUIndexType::U16 => {
+                    let data = self.index_data as *mut u16;
+                    *data.add(self.index_idx) = index.try_into().unwrap();
+                }
+                GPUIndexType::U32 => {
+                    let data = self.index_data as *mut u32;
+                    *data.add(self.index_idx) = index;
+                }
+            }
+        }
+
+        self.index_idx += 1;
+    }
+
+    fn set_instance_attr_value(&mut self, attr_id: usize, values: &[f32]) {
+        assert!(self.instance_len.is_some());
+        assert!(self.instance_idx < self.instance_len.unwrap());
+        self.unassigned_instance_attr_bits &= !(1 << attr_id);
+        let attr = &self.instance_format.attrs[attr_id];
+        // SAFETY: the instance data points to the current instance
+        unsafe {
+            write_attr_f32(self.instance_data, attr, values);
+        }
+    }
+
+    pub fn instance_attr_1f(&mut self, attr_id: usize, x: f32) {
+        self.set_instance_attr_value(attr_id, &[x]);
+    }
+
+    pub fn instance_attr_2f(&mut self, attr_id: usize, x: f32, y: f32) {
+        self.set_instance_attr_value(attr_id, &[x, y]);
+    }
+
+    pub fn instance_attr_3f(&mut self, attr_id: usize, x: f32, y: f32, z: f32) {
+        self.set_instance_attr_value(attr_id, &[x, y, z]);
+    }
+
+    pub fn instance_attr_4f(&mut self, attr_id: usize, x: f32, y: f32, z: f32, w: f32) {
+        self.set_instance_attr_value(attr_id, &[x, y, z, w]);
+    }
+
+    /// End the current instance, similar to the `vertex_*()`
+    /// functions, any per instance attribute not given a value is
+    /// taken from the previous instance.
+    pub fn end_instance(&mut self) {
+        assert!(self.instance_len.is_some());
+
+        if self.unassigned_instance_attr_bits != 0 {
+            assert!(self.instance_idx > 0);
+
+            for (attr_index, a) in self.instance_format.attrs.iter().enumerate() {
+                if (self.unassigned_instance_attr_bits >> attr_index) & 1 != 0 {
+                    unsafe {
+                        let data = self.instance_data.offset(a.offset.into());
+                        let offset: isize = self.instance_format.stride.try_into().unwrap();
+                        std::ptr::copy_nonoverlapping(data.offset(-offset), data, a.sz.into());
+                    }
+                }
+            }
+        }
+
+        self.instance_idx += 1;
+        if !self.instance_format.attrs.is_empty() {
+            unsafe {
+                self.instance_data = self
+                    .instance_data
+                    .offset(self.instance_format.stride.try_into().unwrap());
+            }
+        }
+        self.unassigned_instance_attr_bits = self.instance_attr_binding.enabled_bits;
+    }
+
     pub fn vertex_2f(&mut self, attr_id: usize, x: f32, y: f32) {
         self.attr_2f(attr_id, x, y);
         self.end_vertex();
@@ -727,3 +1316,143 @@ impl Drop for GPUImmediate {
         gpu_vao_free(&self.vao_id);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Get a packed format with the given attributes.
+    fn packed_format(attrs: &[(GPUVertCompType, usize, GPUVertFetchMode)]) -> GPUVertFormat {
+        let mut format = GPUVertFormat::new();
+        attrs
+            .iter()
+            .enumerate()
+            .for_each(|(i, (comp_type, comp_len, fetch_mode))| {
+                format.add_attribute(format!("attr{}\0", i), *comp_type, *comp_len, *fetch_mode);
+            });
+        format.ensure_packed();
+        format
+    }
+
+    #[test]
+    fn index_buffer_size() {
+        assert_eq!(GPUIndexType::U16.buffer_size(6), 12);
+        assert_eq!(GPUIndexType::U32.buffer_size(6), 24);
+    }
+
+    #[test]
+    fn instance_buffer_size() {
+        // vec2 position and normalized u8 color
+        let format = packed_format(&[
+            (GPUVertCompType::F32, 2, GPUVertFetchMode::Float),
+            (GPUVertCompType::U8, 4, GPUVertFetchMode::IntToFloatUnit),
+        ]);
+        assert_eq!(format.get_stride(), 12);
+        assert_eq!(format.get_attr_offset_and_size(1), (8, 4));
+        assert_eq!(format.vertex_buffer_size(1000), 12000);
+
+        // 3 u8 components are padded out to 4 bytes
+        let format = packed_format(&[
+            (GPUVertCompType::U8, 3, GPUVertFetchMode::IntToFloatUnit),
+            (GPUVertCompType::F32, 1, GPUVertFetchMode::Float),
+        ]);
+        assert_eq!(format.get_attr_offset_and_size(1), (4, 4));
+        assert_eq!(format.get_stride(), 8);
+    }
+
+    #[test]
+    fn stream_range_allocates_and_aligns() {
+        // the first map allocates the buffer
+        let range = GPUStreamRange::new(0, 0, GPUIndexType::U16.buffer_size(7), 2);
+        assert_eq!(
+            range,
+            GPUStreamRange {
+                size: IMM_DEFAULT_BUFFER_SIZE,
+                offset: 0,
+                orphan: true,
+            }
+        );
+
+        // u32 indices after 14 bytes of u16 indices are aligned to 4
+        let range = GPUStreamRange::new(range.size, 14, GPUIndexType::U32.buffer_size(6), 4);
+        assert_eq!(
+            range,
+            GPUStreamRange {
+                size: IMM_DEFAULT_BUFFER_SIZE,
+                offset: 16,
+                orphan: false,
+            }
+        );
+
+        // instances are aligned to the stride
+        let range = GPUStreamRange::new(range.size, 40, 12 * 10, 12);
+        assert_eq!(range.offset, 48);
+        assert!(!range.orphan);
+    }
+
+    #[test]
+    fn stream_range_orphans() {
+        // out of space, start over in new storage
+        let range =
+            GPUStreamRange::new(IMM_DEFAULT_BUFFER_SIZE, IMM_DEFAULT_BUFFER_SIZE - 8, 12, 4);
+        assert_eq!(
+            range,
+            GPUStreamRange {
+                size: IMM_DEFAULT_BUFFER_SIZE,
+                offset: 0,
+                orphan: true,
+            }
+        );
+
+        // grow to fit a large draw
+        let large = IMM_DEFAULT_BUFFER_SIZE * 2 + 4;
+        let range = GPUStreamRange::new(IMM_DEFAULT_BUFFER_SIZE, 0, large, 4);
+        assert_eq!((range.size, range.offset, range.orphan), (large, 0, true));
+
+        // shrink back once the draws are small again
+        let range = GPUStreamRange::new(large, large, 64, 4);
+        assert_eq!(
+            (range.size, range.offset, range.orphan),
+            (IMM_DEFAULT_BUFFER_SIZE, 0, true)
+        );
+    }
+
+    #[test]
+    fn write_attr_f32_at_offset() {
+        let format = packed_format(&[
+            (GPUVertCompType::U8, 4, GPUVertFetchMode::IntToFloatUnit),
+            (GPUVertCompType::F32, 2, GPUVertFetchMode::Float),
+        ]);
+        let mut vertex = vec![0_u8; format.get_stride()];
+        unsafe {
+            write_attr_f32(vertex.as_mut_ptr(), &format.attrs[1], &[1.5, -2.0]);
+        }
+        let expected = [
+            &[0; 4][..],
+            &1.5_f32.to_ne_bytes(),
+            &(-2.0_f32).to_ne_bytes(),
+        ]
+        .concat();
+        assert_eq!(vertex, expected);
+    }
+
+    #[test]
+    #[should_panic(expected = "attribute attr0 is not F32")]
+    fn write_attr_f32_rejects_integer_attr() {
+        let format = packed_format(&[(GPUVertCompType::U8, 4, GPUVertFetchMode::IntToFloatUnit)]);
+        let mut vertex = vec![0_u8; format.get_stride()];
+        unsafe {
+            write_attr_f32(vertex.as_mut_ptr(), &format.attrs[0], &[1.0]);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "has 2 components, given 3 values")]
+    fn write_attr_f32_rejects_extra_values() {
+        let format = packed_format(&[(GPUVertCompType::F32, 2, GPUVertFetchMode::Float)]);
+        let mut vertex = vec![0_u8; format.get_stride()];
+        unsafe {
+            write_attr_f32(vertex.as_mut_ptr(), &format.attrs[0], &[1.0, 2.0, 3.0]);
+        }
+    }
+}
@@ -1,19 +1,177 @@
-use egui::{Event, Pos2, RawInput};
+use egui::{
+    epaint::ahash::{AHashMap, AHashSet},
+    Event, Pos2, RawInput,
+};
 use glfw::{Key, MouseButton};
 
+#[cfg(target_os = "linux")]
+use crate::scancode;
+use crate::util::pixels_per_screen_coord;
+
+/// Modifier key used for [`egui::Modifiers::command`], that is the
+/// modifier of shortcuts such as copy (`command` + `C`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandModifier {
+    /// The control key, default on platforms other than macOS.
+    Control,
+    /// The super key (command key on macOS), default on macOS.
+    Super,
+}
+
+impl Default for CommandModifier {
+    fn default() -> Self {
+        if cfg!(target_os = "macos") {
+            Self::Super
+        } else {
+            Self::Control
+        }
+    }
+}
+
+/// How the scroll deltas of GLFW are sent to egui.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScrollConfig {
+    /// Unit of the scroll deltas of mouse wheels.
+    pub unit: egui::MouseWheelUnit,
+    /// Multiplier of the scroll deltas per axis, use a negative value
+    /// to invert an axis.
+    pub multiplier: egui::Vec2,
+    /// Scroll horizontally while shift is held.
+    pub shift_to_horizontal: bool,
+    /// Detect high resolution (touchpad) scroll deltas and send them
+    /// in points instead of [`Self::unit`]. Mouse wheels report
+    /// whole steps while touchpads report fractional deltas.
+    pub detect_touchpad: bool,
+    /// Points per high resolution scroll delta, see
+    /// [`Self::detect_touchpad`].
+    pub touchpad_points_per_delta: f32,
+}
+
+impl Default for ScrollConfig {
+    fn default() -> Self {
+        Self {
+            unit: egui::MouseWheelUnit::Line,
+            multiplier: egui::Vec2::splat(1.0),
+            shift_to_horizontal: true,
+            detect_touchpad: true,
+            // GLFW scales the precise deltas of macOS by 0.1
+            touchpad_points_per_delta: 10.0,
+        }
+    }
+}
+
+/// Screen related state of a window that is costly to query (the
+/// monitors are enumerated), so it is queried again only once the
+/// window moves, is resized or its content scale changes.
+#[derive(Debug, Clone, Copy)]
+struct WindowScreen {
+    /// Framebuffer pixels per screen coordinate of the window.
+    pixels_per_screen_coord: f32,
+    /// Rect of the monitor the window is on in screen coordinates.
+    monitor_rect: Option<egui::Rect>,
+}
+
+impl WindowScreen {
+    /// Query the [`WindowScreen`] of `window`.
+    fn query(window: &glfw::Window) -> Self {
+        // the window is on the monitor that contains its center
+        let (x, y) = window.get_pos();
+        let (width, height) = window.get_size();
+        let center = egui::pos2(
+            x as f32 + width as f32 / 2.0,
+            y as f32 + height as f32 / 2.0,
+        );
+        let monitor_rect = window.glfw.clone().with_connected_monitors(|_, monitors| {
+            monitors.iter().find_map(|monitor| {
+                let video_mode = monitor.get_video_mode()?;
+                let (x, y) = monitor.get_pos();
+                let monitor_rect = egui::Rect::from_min_size(
+                    egui::pos2(x as f32, y as f32),
+                    egui::vec2(video_mode.width as f32, video_mode.height as f32),
+                );
+                monitor_rect.contains(center).then_some(monitor_rect)
+            })
+        });
+
+        Self {
+            pixels_per_screen_coord: pixels_per_screen_coord(window),
+            monitor_rect,
+        }
+    }
+}
+
 pub struct Input {
     raw_input: RawInput,
+    /// Cached [`WindowScreen`] of the window, [`None`] if it must be
+    /// queried again.
+    screen: Option<WindowScreen>,
+    /// Position of the cursor of the last [`glfw::WindowEvent::CursorPos`]
+    /// in screen coordinates, the position of the mouse button
+    /// events. It is not queried from the window so that a replay of
+    /// the events does not depend on the cursor of the window.
+    cursor_pos: Option<(f64, f64)>,
+    /// How the scroll deltas are sent to egui.
+    scroll_config: ScrollConfig,
+    /// Modifier key used for [`egui::Modifiers::command`].
+    command_modifier: CommandModifier,
+    /// Modifier keys that are held down.
+    pressed_modifiers: AHashSet<glfw::Key>,
+    /// Keys that are held down by their scancode, along with the
+    /// logical and physical key of the press sent to egui.
+    pressed_keys: AHashMap<glfw::Scancode, (egui::Key, Option<egui::Key>)>,
 }
 
 impl Input {
     /// Create a new [`Input`] with the given pixels per point.
     pub fn new(pixels_per_point: f32) -> Self {
-        let mut raw_input = RawInput::default();
-        raw_input
-            .viewports
-            .values_mut()
-            .for_each(|viewport| viewport.native_pixels_per_point = Some(pixels_per_point));
-        Self { raw_input }
+        Self::for_viewport(egui::ViewportId::ROOT, pixels_per_point)
+    }
+
+    /// Create a new [`Input`] for the viewport `viewport_id` with the
+    /// given pixels per point.
+    pub fn for_viewport(viewport_id: egui::ViewportId, pixels_per_point: f32) -> Self {
+        let raw_input = RawInput {
+            viewport_id,
+            viewports: std::iter::once((
+                viewport_id,
+                egui::ViewportInfo {
+                    native_pixels_per_point: Some(pixels_per_point),
+                    ..Default::default()
+                },
+            ))
+            .collect(),
+            ..Default::default()
+        };
+        Self {
+            raw_input,
+            screen: None,
+            cursor_pos: None,
+            scroll_config: ScrollConfig::default(),
+            command_modifier: CommandModifier::default(),
+            pressed_modifiers: AHashSet::new(),
+            pressed_keys: AHashMap::new(),
+        }
+    }
+
+    /// Set the modifier key used for [`egui::Modifiers::command`].
+    pub fn set_command_modifier(&mut self, command_modifier: CommandModifier) {
+        self.command_modifier = command_modifier;
+        self.update_modifiers();
+    }
+
+    /// Get the modifier key used for [`egui::Modifiers::command`].
+    pub fn get_command_modifier(&self) -> CommandModifier {
+        self.command_modifier
+    }
+
+    /// Set how the scroll deltas are sent to egui.
+    pub fn set_scroll_config(&mut self, scroll_config: ScrollConfig) {
+        self.scroll_config = scroll_config;
+    }
+
+    /// Get how the scroll deltas are sent to egui.
+    pub fn get_scroll_config(&self) -> ScrollConfig {
+        self.scroll_config
     }
 
     /// Set the pixels per point.
@@ -23,6 +181,16 @@ impl Input {
         });
     }
 
+    /// Get the native pixels per point, that is the content scale of
+    /// the window without egui's zoom factor.
+    pub fn get_native_pixels_per_point(&self) -> f32 {
+        self.raw_input
+            .viewports
+            .get(&self.raw_input.viewport_id)
+            .and_then(|viewport| viewport.native_pixels_per_point)
+            .unwrap_or(1.0)
+    }
+
     /// Refer to egui's RawInput for details on take
     pub fn take(&mut self) -> RawInput {
         self.raw_input.take()
@@ -43,177 +211,386 @@ impl Input {
             MouseButton::Button1 => Some(egui::PointerButton::Primary),
             MouseButton::Button2 => Some(egui::PointerButton::Secondary),
             MouseButton::Button3 => Some(egui::PointerButton::Middle),
+            MouseButton::Button4 => Some(egui::PointerButton::Extra1),
+            MouseButton::Button5 => Some(egui::PointerButton::Extra2),
             _ => None,
         }
     }
 
     #[inline]
-    fn get_modifier(modifiers: &glfw::Modifiers) -> egui::Modifiers {
-        // TODO(ish): handle mac os properly
+    fn get_modifier(&self, modifiers: &glfw::Modifiers) -> egui::Modifiers {
+        self.to_egui_modifiers(
+            modifiers.contains(glfw::Modifiers::Shift),
+            modifiers.contains(glfw::Modifiers::Control),
+            modifiers.contains(glfw::Modifiers::Alt),
+            modifiers.contains(glfw::Modifiers::Super),
+        )
+    }
+
+    /// Get the [`egui::Modifiers`] for the state of the modifier keys.
+    fn to_egui_modifiers(
+        &self,
+        shift: bool,
+        ctrl: bool,
+        alt: bool,
+        super_: bool,
+    ) -> egui::Modifiers {
         egui::Modifiers {
-            alt: modifiers.contains(glfw::Modifiers::Alt),
-            ctrl: modifiers.contains(glfw::Modifiers::Control),
-            shift: modifiers.contains(glfw::Modifiers::Shift),
-            mac_cmd: false,
-            command: modifiers.contains(glfw::Modifiers::Control),
+            alt,
+            ctrl,
+            shift,
+            // egui expects `mac_cmd` to be set only on macOS
+            mac_cmd: cfg!(target_os = "macos") && super_,
+            command: match self.command_modifier {
+                CommandModifier::Control => ctrl,
+                CommandModifier::Super => super_,
+            },
+        }
+    }
+
+    /// Update [`RawInput::modifiers`] from the modifier keys that are
+    /// held down.
+    fn update_modifiers(&mut self) {
+        let is_pressed = |left, right| {
+            self.pressed_modifiers.contains(&left) || self.pressed_modifiers.contains(&right)
+        };
+        self.raw_input.modifiers = self.to_egui_modifiers(
+            is_pressed(Key::LeftShift, Key::RightShift),
+            is_pressed(Key::LeftControl, Key::RightControl),
+            is_pressed(Key::LeftAlt, Key::RightAlt),
+            is_pressed(Key::LeftSuper, Key::RightSuper),
+        );
+    }
+
+    /// Release the keys and modifiers that are held down. Needed when
+    /// the window loses focus since the window does not receive the
+    /// key releases then.
+    fn release_keys(&mut self) {
+        self.pressed_modifiers.clear();
+        self.update_modifiers();
+        let releases = self
+            .pressed_keys
+            .drain()
+            .map(|(_, (key, physical_key))| Event::Key {
+                key,
+                physical_key,
+                pressed: false,
+                repeat: false,
+                modifiers: egui::Modifiers::default(),
+            });
+        self.raw_input.events.extend(releases);
+    }
+
+    /// Get the [`egui::Event::MouseWheel`] for the GLFW scroll
+    /// deltas as per the [`ScrollConfig`].
+    fn get_scroll_event(&self, x: f64, y: f64) -> Event {
+        let config = &self.scroll_config;
+        let is_touchpad = config.detect_touchpad && (x.fract() != 0.0 || y.fract() != 0.0);
+        let delta = egui::vec2(x as _, y as _) * config.multiplier;
+        let (unit, delta) = if is_touchpad {
+            (
+                egui::MouseWheelUnit::Point,
+                delta * config.touchpad_points_per_delta,
+            )
+        } else {
+            (config.unit, delta)
+        };
+
+        // egui scrolls horizontally while shift is held
+        let mut modifiers = self.raw_input.modifiers;
+        if !config.shift_to_horizontal {
+            modifiers.shift = false;
+        }
+
+        Event::MouseWheel {
+            unit,
+            delta,
+            modifiers,
         }
     }
 
     #[inline]
-    fn get_cur_pos(window: &glfw::Window, pixels_per_point: f32) -> Pos2 {
-        let pos = window.get_cursor_pos();
+    fn get_cur_pos(cursor_pos: (f64, f64), pixels_per_point: f32) -> Pos2 {
         egui::pos2(
-            pos.0 as f32 / pixels_per_point,
-            pos.1 as f32 / pixels_per_point,
+            cursor_pos.0 as f32 / pixels_per_point,
+            cursor_pos.1 as f32 / pixels_per_point,
         )
     }
 
     /// Get the corresponding [`egui::Key`] for the [`glfw::Key`].
-    fn get_key(key: &glfw::Key) -> Option<egui::Key> {
-        Some(match key {
-            glfw::Key::Down => egui::Key::ArrowDown,
-            glfw::Key::Left => egui::Key::ArrowLeft,
-            glfw::Key::Right => egui::Key::ArrowRight,
-            glfw::Key::Up => egui::Key::ArrowUp,
-
-            glfw::Key::Escape => egui::Key::Escape,
-            glfw::Key::Tab => egui::Key::Tab,
-            glfw::Key::Backspace => egui::Key::Backspace,
-            glfw::Key::Enter => egui::Key::Enter,
-            glfw::Key::Space => egui::Key::Space,
-
-            glfw::Key::Insert => egui::Key::Insert,
-            glfw::Key::Delete => egui::Key::Delete,
-            glfw::Key::Home => egui::Key::Home,
-            glfw::Key::End => egui::Key::End,
-            glfw::Key::PageUp => egui::Key::PageUp,
-            glfw::Key::PageDown => egui::Key::PageDown,
-
-            // egui::Key::Copy,
-            // egui::Key::Cut,
-            // egui::Key::Paste,
-
-            // egui::Key::Colon,
-            glfw::Key::Comma => egui::Key::Comma,
-            glfw::Key::Backslash => egui::Key::Backslash,
-            glfw::Key::Slash => egui::Key::Slash,
-            // egui::Key::Pipe
-            // egui::Key::Questionmark,
-            glfw::Key::LeftBracket => egui::Key::OpenBracket,
-            glfw::Key::RightBracket => egui::Key::CloseBracket,
-            // egui::Key::Backtick,
-            glfw::Key::Minus => egui::Key::Minus,
-            glfw::Key::Period => egui::Key::Period,
-            // egui::Key::Plus,
-            glfw::Key::Equal => egui::Key::Equals,
-            glfw::Key::Semicolon => egui::Key::Semicolon,
-
-            glfw::Key::Num0 => egui::Key::Num0,
-            glfw::Key::Num1 => egui::Key::Num1,
-            glfw::Key::Num2 => egui::Key::Num2,
-            glfw::Key::Num3 => egui::Key::Num3,
-            glfw::Key::Num4 => egui::Key::Num4,
-            glfw::Key::Num5 => egui::Key::Num5,
-            glfw::Key::Num6 => egui::Key::Num6,
-            glfw::Key::Num7 => egui::Key::Num7,
-            glfw::Key::Num8 => egui::Key::Num8,
-            glfw::Key::Num9 => egui::Key::Num9,
-
-            glfw::Key::A => egui::Key::A,
-            glfw::Key::B => egui::Key::B,
-            glfw::Key::C => egui::Key::C,
-            glfw::Key::D => egui::Key::D,
-            glfw::Key::E => egui::Key::E,
-            glfw::Key::F => egui::Key::F,
-            glfw::Key::G => egui::Key::G,
-            glfw::Key::H => egui::Key::H,
-            glfw::Key::I => egui::Key::I,
-            glfw::Key::J => egui::Key::J,
-            glfw::Key::K => egui::Key::K,
-            glfw::Key::L => egui::Key::L,
-            glfw::Key::M => egui::Key::M,
-            glfw::Key::N => egui::Key::N,
-            glfw::Key::O => egui::Key::O,
-            glfw::Key::P => egui::Key::P,
-            glfw::Key::Q => egui::Key::Q,
-            glfw::Key::R => egui::Key::R,
-            glfw::Key::S => egui::Key::S,
-            glfw::Key::T => egui::Key::T,
-            glfw::Key::U => egui::Key::U,
-            glfw::Key::V => egui::Key::V,
-            glfw::Key::W => egui::Key::W,
-            glfw::Key::X => egui::Key::X,
-            glfw::Key::Y => egui::Key::Y,
-            glfw::Key::Z => egui::Key::Z,
-
-            glfw::Key::F1 => egui::Key::F1,
-            glfw::Key::F2 => egui::Key::F2,
-            glfw::Key::F3 => egui::Key::F3,
-            glfw::Key::F4 => egui::Key::F4,
-            glfw::Key::F5 => egui::Key::F5,
-            glfw::Key::F6 => egui::Key::F6,
-            glfw::Key::F7 => egui::Key::F7,
-            glfw::Key::F8 => egui::Key::F8,
-            glfw::Key::F9 => egui::Key::F9,
-            glfw::Key::F10 => egui::Key::F10,
-            glfw::Key::F11 => egui::Key::F11,
-            glfw::Key::F12 => egui::Key::F12,
-            glfw::Key::F13 => egui::Key::F13,
-            glfw::Key::F14 => egui::Key::F14,
-            glfw::Key::F15 => egui::Key::F15,
-            glfw::Key::F16 => egui::Key::F16,
-            glfw::Key::F17 => egui::Key::F17,
-            glfw::Key::F18 => egui::Key::F18,
-            glfw::Key::F19 => egui::Key::F19,
-            glfw::Key::F20 => egui::Key::F20,
-            glfw::Key::F21 => egui::Key::F21,
-            glfw::Key::F22 => egui::Key::F22,
-            glfw::Key::F23 => egui::Key::F23,
-            glfw::Key::F24 => egui::Key::F24,
-            glfw::Key::F25 => egui::Key::F25,
-            // egui::Key::F26,
-            // egui::Key::F27,
-            // egui::Key::F28,
-            // egui::Key::F29,
-            // egui::Key::F30,
-            // egui::Key::F31,
-            // egui::Key::F32,
-            // egui::Key::F33,
-            // egui::Key::F34,
-            // egui::Key::F35,
-            _ => return None,
-        })
+    ///
+    /// The keypad keys depend on NumLock in `modifiers`, see
+    /// [`glfw::Window::set_store_lock_key_mods()`].
+    ///
+    /// Keys that have no egui equivalent map to [`None`]. Shifted
+    /// characters (for example [`egui::Key::Colon`]) are not GLFW
+    /// keys, they are mapped from the layout specific key name, see
+    /// [`Self::get_logical_key()`].
+    fn get_key(key: &glfw::Key, modifiers: &glfw::Modifiers) -> Option<egui::Key> {
+        let num_lock = modifiers.contains(glfw::Modifiers::NumLock);
+        // no wildcard so that new GLFW keys are not unmapped
+        // unintentionally
+        match key {
+            glfw::Key::Down => Some(egui::Key::ArrowDown),
+            glfw::Key::Left => Some(egui::Key::ArrowLeft),
+            glfw::Key::Right => Some(egui::Key::ArrowRight),
+            glfw::Key::Up => Some(egui::Key::ArrowUp),
+
+            glfw::Key::Escape => Some(egui::Key::Escape),
+            glfw::Key::Tab => Some(egui::Key::Tab),
+            glfw::Key::Backspace => Some(egui::Key::Backspace),
+            glfw::Key::Enter => Some(egui::Key::Enter),
+            glfw::Key::Space => Some(egui::Key::Space),
+
+            glfw::Key::Insert => Some(egui::Key::Insert),
+            glfw::Key::Delete => Some(egui::Key::Delete),
+            glfw::Key::Home => Some(egui::Key::Home),
+            glfw::Key::End => Some(egui::Key::End),
+            glfw::Key::PageUp => Some(egui::Key::PageUp),
+            glfw::Key::PageDown => Some(egui::Key::PageDown),
+
+            glfw::Key::Apostrophe => Some(egui::Key::Quote),
+            glfw::Key::Comma => Some(egui::Key::Comma),
+            glfw::Key::Backslash => Some(egui::Key::Backslash),
+            glfw::Key::Slash => Some(egui::Key::Slash),
+            glfw::Key::LeftBracket => Some(egui::Key::OpenBracket),
+            glfw::Key::RightBracket => Some(egui::Key::CloseBracket),
+            glfw::Key::GraveAccent => Some(egui::Key::Backtick),
+            glfw::Key::Minus => Some(egui::Key::Minus),
+            glfw::Key::Period => Some(egui::Key::Period),
+            glfw::Key::Equal => Some(egui::Key::Equals),
+            glfw::Key::Semicolon => Some(egui::Key::Semicolon),
+
+            glfw::Key::Num0 => Some(egui::Key::Num0),
+            glfw::Key::Num1 => Some(egui::Key::Num1),
+            glfw::Key::Num2 => Some(egui::Key::Num2),
+            glfw::Key::Num3 => Some(egui::Key::Num3),
+            glfw::Key::Num4 => Some(egui::Key::Num4),
+            glfw::Key::Num5 => Some(egui::Key::Num5),
+            glfw::Key::Num6 => Some(egui::Key::Num6),
+            glfw::Key::Num7 => Some(egui::Key::Num7),
+            glfw::Key::Num8 => Some(egui::Key::Num8),
+            glfw::Key::Num9 => Some(egui::Key::Num9),
+
+            glfw::Key::A => Some(egui::Key::A),
+            glfw::Key::B => Some(egui::Key::B),
+            glfw::Key::C => Some(egui::Key::C),
+            glfw::Key::D => Some(egui::Key::D),
+            glfw::Key::E => Some(egui::Key::E),
+            glfw::Key::F => Some(egui::Key::F),
+            glfw::Key::G => Some(egui::Key::G),
+            glfw::Key::H => Some(egui::Key::H),
+            glfw::Key::I => Some(egui::Key::I),
+            glfw::Key::J => Some(egui::Key::J),
+            glfw::Key::K => Some(egui::Key::K),
+            glfw::Key::L => Some(egui::Key::L),
+            glfw::Key::M => Some(egui::Key::M),
+            glfw::Key::N => Some(egui::Key::N),
+            glfw::Key::O => Some(egui::Key::O),
+            glfw::Key::P => Some(egui::Key::P),
+            glfw::Key::Q => Some(egui::Key::Q),
+            glfw::Key::R => Some(egui::Key::R),
+            glfw::Key::S => Some(egui::Key::S),
+            glfw::Key::T => Some(egui::Key::T),
+            glfw::Key::U => Some(egui::Key::U),
+            glfw::Key::V => Some(egui::Key::V),
+            glfw::Key::W => Some(egui::Key::W),
+            glfw::Key::X => Some(egui::Key::X),
+            glfw::Key::Y => Some(egui::Key::Y),
+            glfw::Key::Z => Some(egui::Key::Z),
+
+            glfw::Key::F1 => Some(egui::Key::F1),
+            glfw::Key::F2 => Some(egui::Key::F2),
+            glfw::Key::F3 => Some(egui::Key::F3),
+            glfw::Key::F4 => Some(egui::Key::F4),
+            glfw::Key::F5 => Some(egui::Key::F5),
+            glfw::Key::F6 => Some(egui::Key::F6),
+            glfw::Key::F7 => Some(egui::Key::F7),
+            glfw::Key::F8 => Some(egui::Key::F8),
+            glfw::Key::F9 => Some(egui::Key::F9),
+            glfw::Key::F10 => Some(egui::Key::F10),
+            glfw::Key::F11 => Some(egui::Key::F11),
+            glfw::Key::F12 => Some(egui::Key::F12),
+            glfw::Key::F13 => Some(egui::Key::F13),
+            glfw::Key::F14 => Some(egui::Key::F14),
+            glfw::Key::F15 => Some(egui::Key::F15),
+            glfw::Key::F16 => Some(egui::Key::F16),
+            glfw::Key::F17 => Some(egui::Key::F17),
+            glfw::Key::F18 => Some(egui::Key::F18),
+            glfw::Key::F19 => Some(egui::Key::F19),
+            glfw::Key::F20 => Some(egui::Key::F20),
+            glfw::Key::F21 => Some(egui::Key::F21),
+            glfw::Key::F22 => Some(egui::Key::F22),
+            glfw::Key::F23 => Some(egui::Key::F23),
+            glfw::Key::F24 => Some(egui::Key::F24),
+            // GLFW has no keys beyond F25
+            glfw::Key::F25 => Some(egui::Key::F25),
+
+            // keypad, egui does not distinguish the keypad keys
+            glfw::Key::Kp0 if num_lock => Some(egui::Key::Num0),
+            glfw::Key::Kp0 => Some(egui::Key::Insert),
+            glfw::Key::Kp1 if num_lock => Some(egui::Key::Num1),
+            glfw::Key::Kp1 => Some(egui::Key::End),
+            glfw::Key::Kp2 if num_lock => Some(egui::Key::Num2),
+            glfw::Key::Kp2 => Some(egui::Key::ArrowDown),
+            glfw::Key::Kp3 if num_lock => Some(egui::Key::Num3),
+            glfw::Key::Kp3 => Some(egui::Key::PageDown),
+            glfw::Key::Kp4 if num_lock => Some(egui::Key::Num4),
+            glfw::Key::Kp4 => Some(egui::Key::ArrowLeft),
+            glfw::Key::Kp5 if num_lock => Some(egui::Key::Num5),
+            glfw::Key::Kp5 => None,
+            glfw::Key::Kp6 if num_lock => Some(egui::Key::Num6),
+            glfw::Key::Kp6 => Some(egui::Key::ArrowRight),
+            glfw::Key::Kp7 if num_lock => Some(egui::Key::Num7),
+            glfw::Key::Kp7 => Some(egui::Key::Home),
+            glfw::Key::Kp8 if num_lock => Some(egui::Key::Num8),
+            glfw::Key::Kp8 => Some(egui::Key::ArrowUp),
+            glfw::Key::Kp9 if num_lock => Some(egui::Key::Num9),
+            glfw::Key::Kp9 => Some(egui::Key::PageUp),
+            glfw::Key::KpDecimal if num_lock => Some(egui::Key::Period),
+            glfw::Key::KpDecimal => Some(egui::Key::Delete),
+            glfw::Key::KpDivide => Some(egui::Key::Slash),
+            glfw::Key::KpSubtract => Some(egui::Key::Minus),
+            glfw::Key::KpAdd => Some(egui::Key::Plus),
+            glfw::Key::KpEnter => Some(egui::Key::Enter),
+            glfw::Key::KpEqual => Some(egui::Key::Equals),
+            // egui has no asterisk key
+            glfw::Key::KpMultiply => None,
+
+            // modifiers are tracked separately
+            glfw::Key::LeftShift
+            | glfw::Key::LeftControl
+            | glfw::Key::LeftAlt
+            | glfw::Key::LeftSuper
+            | glfw::Key::RightShift
+            | glfw::Key::RightControl
+            | glfw::Key::RightAlt
+            | glfw::Key::RightSuper => None,
+
+            // no egui equivalent, the non-US keys are mapped from
+            // their layout specific name
+            glfw::Key::World1
+            | glfw::Key::World2
+            | glfw::Key::CapsLock
+            | glfw::Key::ScrollLock
+            | glfw::Key::NumLock
+            | glfw::Key::PrintScreen
+            | glfw::Key::Pause
+            | glfw::Key::Menu
+            | glfw::Key::Unknown => None,
+        }
     }
 
     /// Get the corresponding physical [`egui::Key`] for the
-    /// [`glfw::Scancode`].
-    fn get_physical_key(_scan_code: &glfw::Scancode) -> Option<egui::Key> {
-        // TODO: need to figure out what scancode correspondings to
-        // what key
-        None
+    /// [`glfw::Scancode`] of the [`glfw::Key`].
+    fn get_physical_key(key: &glfw::Key, scancode: &glfw::Scancode) -> Option<egui::Key> {
+        #[cfg(target_os = "linux")]
+        if let Some(physical_key) =
+            glfw::get_key_scancode(Some(glfw::Key::Escape)).and_then(|escape_scancode| {
+                scancode::physical_key_from_scancode(*scancode, escape_scancode)
+            })
+        {
+            return Some(physical_key);
+        }
+        #[cfg(not(target_os = "linux"))]
+        let _ = scancode;
+
+        // GLFW keys are named after their position on the US layout
+        Self::get_key(key, &glfw::Modifiers::NumLock)
     }
 
+    /// Get the corresponding logical [`egui::Key`] for the
+    /// [`glfw::Key`] as per the current keyboard layout.
+    ///
+    /// GLFW keys are named after their position on the US layout, the
+    /// layout specific name of printable keys is used when egui
+    /// supports it (for example `A` for the [`glfw::Key::Q`] on
+    /// AZERTY).
+    fn get_logical_key(
+        key: &glfw::Key,
+        scancode: &glfw::Scancode,
+        modifiers: &glfw::Modifiers,
+    ) -> Option<egui::Key> {
+        // the keypad does not depend on the layout but on NumLock
+        let is_keypad = (glfw::Key::Kp0..=glfw::Key::KpEqual).contains(key);
+        (!is_keypad)
+            .then(|| glfw::get_key_name(Some(*key), Some(*scancode)))
+            .flatten()
+            .and_then(|name| egui::Key::from_name(&name))
+            .or_else(|| Self::get_key(key, modifiers))
+            // keys unknown to GLFW, such as the copy key
+            .or_else(|| match key {
+                glfw::Key::Unknown => Self::get_physical_key(key, scancode),
+                _ => None,
+            })
+    }
+
+    /// Handle the [`glfw::WindowEvent`], the state of `window` is
+    /// queried for the events that change it.
     pub fn handle_event(
         &mut self,
         event: &glfw::WindowEvent,
         window: &glfw::Window,
         pixels_per_point: f32,
     ) {
+        if let Some(cursor_pos) = self.get_missing_cursor_pos(event, window) {
+            self.push_window_event(&cursor_pos, pixels_per_point);
+        }
+        self.push_window_event(event, pixels_per_point);
+
+        if let glfw::WindowEvent::Focus(_)
+        | glfw::WindowEvent::Pos(_, _)
+        | glfw::WindowEvent::Size(_, _)
+        | glfw::WindowEvent::Iconify(_)
+        | glfw::WindowEvent::Maximize(_) = event
+        {
+            self.update_viewport_info(window, pixels_per_point);
+        }
+    }
+
+    /// Get the [`glfw::WindowEvent::CursorPos`] of the cursor of
+    /// `window` that must be handled before `event`, a mouse button
+    /// may be pressed before the cursor moves and the button events
+    /// have no position.
+    pub fn get_missing_cursor_pos(
+        &self,
+        event: &glfw::WindowEvent,
+        window: &glfw::Window,
+    ) -> Option<glfw::WindowEvent> {
+        match event {
+            glfw::WindowEvent::MouseButton(..) if self.cursor_pos.is_none() => {
+                let (x, y) = window.get_cursor_pos();
+                Some(glfw::WindowEvent::CursorPos(x, y))
+            }
+            _ => None,
+        }
+    }
+
+    /// Convert the [`glfw::WindowEvent`] to the events of egui
+    /// without querying the state of the window, see
+    /// [`Self::handle_event()`].
+    pub fn push_window_event(&mut self, event: &glfw::WindowEvent, pixels_per_point: f32) {
         let raw_event = match event {
-            glfw::WindowEvent::CursorPos(x, y) => Some(Event::PointerMoved(egui::pos2(
-                *x as f32 / pixels_per_point,
-                *y as f32 / pixels_per_point,
-            ))),
+            glfw::WindowEvent::CursorPos(x, y) => {
+                self.cursor_pos = Some((*x, *y));
+                Some(Event::PointerMoved(Self::get_cur_pos(
+                    (*x, *y),
+                    pixels_per_point,
+                )))
+            }
             glfw::WindowEvent::MouseButton(button, action, modifier) => {
                 let button = Self::button_type(button);
                 match button {
                     Some(button) => {
                         let pressed = Self::is_pressed(action);
                         Some(Event::PointerButton {
-                            pos: Self::get_cur_pos(window, pixels_per_point),
+                            pos: Self::get_cur_pos(
+                                self.cursor_pos.unwrap_or_default(),
+                                pixels_per_point,
+                            ),
                             button,
                             pressed,
-                            modifiers: Self::get_modifier(modifier),
+                            modifiers: self.get_modifier(modifier),
                         })
                     }
                     None => None,
@@ -221,32 +598,49 @@ impl Input {
             }
             glfw::WindowEvent::Key(key, scancode, action, modifiers) => {
                 let pressed = Self::is_pressed(action);
-                match key {
-                    Key::LeftShift | Key::RightShift => self.raw_input.modifiers.shift = pressed,
-                    Key::LeftControl | Key::RightControl => {
-                        self.raw_input.modifiers.ctrl = pressed;
-                        self.raw_input.modifiers.command = pressed;
+                if let Key::LeftShift
+                | Key::RightShift
+                | Key::LeftControl
+                | Key::RightControl
+                | Key::LeftAlt
+                | Key::RightAlt
+                | Key::LeftSuper
+                | Key::RightSuper = key
+                {
+                    if pressed {
+                        self.pressed_modifiers.insert(*key);
+                    } else {
+                        self.pressed_modifiers.remove(key);
                     }
-                    Key::LeftAlt | Key::RightAlt => self.raw_input.modifiers.alt = pressed,
-                    _ => (),
+                    self.update_modifiers();
                 }
-                Self::get_key(key).map(|key| Event::Key {
+
+                let keys = if pressed {
+                    let keys = Self::get_logical_key(key, scancode, modifiers)
+                        .map(|logical_key| (logical_key, Self::get_physical_key(key, scancode)));
+                    if let Some(keys) = keys {
+                        self.pressed_keys.insert(*scancode, keys);
+                    }
+                    keys
+                } else {
+                    // release the keys of the press, the logical key
+                    // may have changed since (NumLock, layout)
+                    self.pressed_keys.remove(scancode).or_else(|| {
+                        Self::get_logical_key(key, scancode, modifiers)
+                            .map(|logical_key| (logical_key, Self::get_physical_key(key, scancode)))
+                    })
+                };
+                keys.map(|(key, physical_key)| Event::Key {
                     key,
-                    physical_key: Self::get_physical_key(scancode),
+                    physical_key,
                     pressed,
-                    repeat: false,
-                    modifiers: Self::get_modifier(modifiers),
-                })
-            }
-            glfw::WindowEvent::Scroll(x, y) => {
-                let delta = egui::vec2(*x as _, *y as _);
-                Some(Event::MouseWheel {
-                    unit: egui::MouseWheelUnit::Line,
-                    delta,
-                    modifiers: self.raw_input.modifiers,
+                    repeat: *action == glfw::Action::Repeat,
+                    modifiers: self.get_modifier(modifiers),
                 })
             }
+            glfw::WindowEvent::Scroll(x, y) => Some(self.get_scroll_event(*x, *y)),
             glfw::WindowEvent::FramebufferSize(width, height) => {
+                self.screen = None;
                 unsafe {
                     gl::Viewport(0, 0, *width, *height);
                 }
@@ -264,13 +658,25 @@ impl Input {
                 }
             }
             glfw::WindowEvent::Char(c) => Some(Event::Text(c.to_string())),
-            glfw::WindowEvent::Pos(_, _) => None,
+            glfw::WindowEvent::Focus(focused) => {
+                if !focused {
+                    self.release_keys();
+                }
+                Some(Event::WindowFocused(*focused))
+            }
+            glfw::WindowEvent::Pos(_, _) | glfw::WindowEvent::Size(_, _) => {
+                self.screen = None;
+                None
+            }
+            glfw::WindowEvent::Iconify(_) | glfw::WindowEvent::Maximize(_) => None,
             glfw::WindowEvent::Refresh => None,
-            glfw::WindowEvent::Focus(_) => None,
             glfw::WindowEvent::CharModifiers(_, _) => None,
-            glfw::WindowEvent::Close => None,
-            glfw::WindowEvent::Size(_, _) => None,
-            glfw::WindowEvent::Iconify(_) => None,
+            glfw::WindowEvent::Close => {
+                // the close can be cancelled with
+                // `egui::ViewportCommand::CancelClose`
+                self.push_viewport_event(egui::ViewportEvent::Close);
+                None
+            }
             glfw::WindowEvent::FileDrop(paths) => {
                 self.raw_input
                     .dropped_files
@@ -280,11 +686,11 @@ impl Input {
                     }));
                 None
             }
-            glfw::WindowEvent::Maximize(_) => None,
             glfw::WindowEvent::ContentScale(x, _y) => {
                 // taking the x scale because egui supports only one
                 // value
                 self.set_pixels_per_point(*x);
+                self.screen = None;
                 None
             }
         };
@@ -302,6 +708,61 @@ impl Input {
         self.raw_input.events.push(event);
     }
 
+    /// Push a [`egui::ViewportEvent`] for the viewport of the input.
+    pub fn push_viewport_event(&mut self, event: egui::ViewportEvent) {
+        self.raw_input
+            .viewports
+            .entry(self.raw_input.viewport_id)
+            .or_default()
+            .events
+            .push(event);
+    }
+
+    /// Update the [`egui::ViewportInfo`] of the viewport of the input
+    /// from the state of `window`.
+    pub fn update_viewport_info(&mut self, window: &glfw::Window, pixels_per_point: f32) {
+        let screen = *self
+            .screen
+            .get_or_insert_with(|| WindowScreen::query(window));
+
+        // GLFW positions and sizes windows in screen coordinates
+        let points_per_screen_coord = screen.pixels_per_screen_coord / pixels_per_point;
+        let to_points = |x: i32, y: i32| egui::vec2(x as f32, y as f32) * points_per_screen_coord;
+
+        let (x, y) = window.get_pos();
+        let (width, height) = window.get_size();
+        let (left, top, right, bottom) = window.get_frame_size();
+        let inner_rect =
+            egui::Rect::from_min_size(to_points(x, y).to_pos2(), to_points(width, height));
+        let outer_rect = egui::Rect::from_min_max(
+            inner_rect.min - to_points(left, top),
+            inner_rect.max + to_points(right, bottom),
+        );
+
+        let fullscreen = window.with_window_mode(|mode| match mode {
+            glfw::WindowMode::FullScreen(_) => true,
+            glfw::WindowMode::Windowed => false,
+        });
+
+        let monitor_size = screen
+            .monitor_rect
+            .map(|monitor_rect| monitor_rect.size() * points_per_screen_coord);
+
+        let viewport = self
+            .raw_input
+            .viewports
+            .entry(self.raw_input.viewport_id)
+            .or_default();
+        viewport.inner_rect = Some(inner_rect);
+        viewport.outer_rect = Some(outer_rect);
+        viewport.minimized = Some(window.is_iconified());
+        viewport.maximized = Some(window.is_maximized());
+        viewport.fullscreen = Some(fullscreen);
+        viewport.focused = Some(window.is_focused());
+        viewport.monitor_size = monitor_size;
+        self.raw_input.focused = window.is_focused();
+    }
+
     /// Set the screen rect from the given screen size in pixels.
     fn set_screen_rect_from_size(
         &mut self,
@@ -335,3 +796,270 @@ impl Input {
         &mut self.raw_input
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Number of [`glfw::Key`]s including [`glfw::Key::Unknown`].
+    const GLFW_KEY_COUNT: usize = 121;
+
+    /// Every [`glfw::Key`] that has an [`egui::Key`], with NumLock on.
+    const MAPPED_KEYS: &[(glfw::Key, egui::Key)] = &[
+        (glfw::Key::Down, egui::Key::ArrowDown),
+        (glfw::Key::Left, egui::Key::ArrowLeft),
+        (glfw::Key::Right, egui::Key::ArrowRight),
+        (glfw::Key::Up, egui::Key::ArrowUp),
+        (glfw::Key::Escape, egui::Key::Escape),
+        (glfw::Key::Tab, egui::Key::Tab),
+        (glfw::Key::Backspace, egui::Key::Backspace),
+        (glfw::Key::Enter, egui::Key::Enter),
+        (glfw::Key::Space, egui::Key::Space),
+        (glfw::Key::Insert, egui::Key::Insert),
+        (glfw::Key::Delete, egui::Key::Delete),
+        (glfw::Key::Home, egui::Key::Home),
+        (glfw::Key::End, egui::Key::End),
+        (glfw::Key::PageUp, egui::Key::PageUp),
+        (glfw::Key::PageDown, egui::Key::PageDown),
+        (glfw::Key::Apostrophe, egui::Key::Quote),
+        (glfw::Key::Comma, egui::Key::Comma),
+        (glfw::Key::Backslash, egui::Key::Backslash),
+        (glfw::Key::Slash, egui::Key::Slash),
+        (glfw::Key::LeftBracket, egui::Key::OpenBracket),
+        (glfw::Key::RightBracket, egui::Key::CloseBracket),
+        (glfw::Key::GraveAccent, egui::Key::Backtick),
+        (glfw::Key::Minus, egui::Key::Minus),
+        (glfw::Key::Period, egui::Key::Period),
+        (glfw::Key::Equal, egui::Key::Equals),
+        (glfw::Key::Semicolon, egui::Key::Semicolon),
+        (glfw::Key::Num0, egui::Key::Num0),
+        (glfw::Key::Num1, egui::Key::Num1),
+        (glfw::Key::Num2, egui::Key::Num2),
+        (glfw::Key::Num3, egui::Key::Num3),
+        (glfw::Key::Num4, egui::Key::Num4),
+        (glfw::Key::Num5, egui::Key::Num5),
+        (glfw::Key::Num6, egui::Key::Num6),
+        (glfw::Key::Num7, egui::Key::Num7),
+        (glfw::Key::Num8, egui::Key::Num8),
+        (glfw::Key::Num9, egui::Key::Num9),
+        (glfw::Key::A, egui::Key::A),
+        (glfw::Key::B, egui::Key::B),
+        (glfw::Key::C, egui::Key::C),
+        (glfw::Key::D, egui::Key::D),
+        (glfw::Key::E, egui::Key::E),
+        (glfw::Key::F, egui::Key::F),
+        (glfw::Key::G, egui::Key::G),
+        (glfw::Key::H, egui::Key::H),
+        (glfw::Key::I, egui::Key::I),
+        (glfw::Key::J, egui::Key::J),
+        (glfw::Key::K, egui::Key::K),
+        (glfw::Key::L, egui::Key::L),
+        (glfw::Key::M, egui::Key::M),
+        (glfw::Key::N, egui::Key::N),
+        (glfw::Key::O, egui::Key::O),
+        (glfw::Key::P, egui::Key::P),
+        (glfw::Key::Q, egui::Key::Q),
+        (glfw::Key::R, egui::Key::R),
+        (glfw::Key::S, egui::Key::S),
+        (glfw::Key::T, egui::Key::T),
+        (glfw::Key::U, egui::Key::U),
+        (glfw::Key::V, egui::Key::V),
+        (glfw::Key::W, egui::Key::W),
+        (glfw::Key::X, egui::Key::X),
+        (glfw::Key::Y, egui::Key::Y),
+        (glfw::Key::Z, egui::Key::Z),
+        (glfw::Key::F1, egui::Key::F1),
+        (glfw::Key::F2, egui::Key::F2),
+        (glfw::Key::F3, egui::Key::F3),
+        (glfw::Key::F4, egui::Key::F4),
+        (glfw::Key::F5, egui::Key::F5),
+        (glfw::Key::F6, egui::Key::F6),
+        (glfw::Key::F7, egui::Key::F7),
+        (glfw::Key::F8, egui::Key::F8),
+        (glfw::Key::F9, egui::Key::F9),
+        (glfw::Key::F10, egui::Key::F10),
+        (glfw::Key::F11, egui::Key::F11),
+        (glfw::Key::F12, egui::Key::F12),
+        (glfw::Key::F13, egui::Key::F13),
+        (glfw::Key::F14, egui::Key::F14),
+        (glfw::Key::F15, egui::Key::F15),
+        (glfw::Key::F16, egui::Key::F16),
+        (glfw::Key::F17, egui::Key::F17),
+        (glfw::Key::F18, egui::Key::F18),
+        (glfw::Key::F19, egui::Key::F19),
+        (glfw::Key::F20, egui::Key::F20),
+        (glfw::Key::F21, egui::Key::F21),
+        (glfw::Key::F22, egui::Key::F22),
+        (glfw::Key::F23, egui::Key::F23),
+        (glfw::Key::F24, egui::Key::F24),
+        (glfw::Key::F25, egui::Key::F25),
+        (glfw::Key::Kp0, egui::Key::Num0),
+        (glfw::Key::Kp1, egui::Key::Num1),
+        (glfw::Key::Kp2, egui::Key::Num2),
+        (glfw::Key::Kp3, egui::Key::Num3),
+        (glfw::Key::Kp4, egui::Key::Num4),
+        (glfw::Key::Kp5, egui::Key::Num5),
+        (glfw::Key::Kp6, egui::Key::Num6),
+        (glfw::Key::Kp7, egui::Key::Num7),
+        (glfw::Key::Kp8, egui::Key::Num8),
+        (glfw::Key::Kp9, egui::Key::Num9),
+        (glfw::Key::KpDecimal, egui::Key::Period),
+        (glfw::Key::KpDivide, egui::Key::Slash),
+        (glfw::Key::KpSubtract, egui::Key::Minus),
+        (glfw::Key::KpAdd, egui::Key::Plus),
+        (glfw::Key::KpEnter, egui::Key::Enter),
+        (glfw::Key::KpEqual, egui::Key::Equals),
+    ];
+
+    /// Every [`glfw::Key`] that intentionally has no [`egui::Key`].
+    const UNMAPPED_KEYS: &[glfw::Key] = &[
+        glfw::Key::KpMultiply,
+        glfw::Key::LeftShift,
+        glfw::Key::LeftControl,
+        glfw::Key::LeftAlt,
+        glfw::Key::LeftSuper,
+        glfw::Key::RightShift,
+        glfw::Key::RightControl,
+        glfw::Key::RightAlt,
+        glfw::Key::RightSuper,
+        glfw::Key::World1,
+        glfw::Key::World2,
+        glfw::Key::CapsLock,
+        glfw::Key::ScrollLock,
+        glfw::Key::NumLock,
+        glfw::Key::PrintScreen,
+        glfw::Key::Pause,
+        glfw::Key::Menu,
+        glfw::Key::Unknown,
+    ];
+
+    #[test]
+    fn native_pixels_per_point() {
+        let mut input = Input::for_viewport(egui::ViewportId::from_hash_of("viewport"), 2.0);
+        assert_eq!(input.get_native_pixels_per_point(), 2.0);
+
+        input.set_pixels_per_point(1.5);
+        assert_eq!(input.get_native_pixels_per_point(), 1.5);
+        assert_eq!(input.take().viewport().native_pixels_per_point, Some(1.5));
+    }
+
+    #[test]
+    fn every_key_is_covered() {
+        let keys: std::collections::BTreeSet<_> = MAPPED_KEYS
+            .iter()
+            .map(|(key, _)| *key)
+            .chain(UNMAPPED_KEYS.iter().copied())
+            .collect();
+        assert_eq!(keys.len(), MAPPED_KEYS.len() + UNMAPPED_KEYS.len());
+        assert_eq!(keys.len(), GLFW_KEY_COUNT);
+    }
+
+    #[test]
+    fn mapped_keys() {
+        MAPPED_KEYS.iter().for_each(|(key, egui_key)| {
+            assert_eq!(
+                Input::get_key(key, &glfw::Modifiers::NumLock),
+                Some(*egui_key),
+                "{:?}",
+                key
+            );
+        });
+    }
+
+    #[test]
+    fn unmapped_keys() {
+        UNMAPPED_KEYS.iter().for_each(|key| {
+            assert_eq!(
+                Input::get_key(key, &glfw::Modifiers::NumLock),
+                None,
+                "{:?}",
+                key
+            );
+            assert_eq!(
+                Input::get_key(key, &glfw::Modifiers::empty()),
+                None,
+                "{:?}",
+                key
+            );
+        });
+    }
+
+    #[test]
+    fn keypad_without_num_lock() {
+        [
+            (glfw::Key::Kp0, Some(egui::Key::Insert)),
+            (glfw::Key::Kp1, Some(egui::Key::End)),
+            (glfw::Key::Kp2, Some(egui::Key::ArrowDown)),
+            (glfw::Key::Kp3, Some(egui::Key::PageDown)),
+            (glfw::Key::Kp4, Some(egui::Key::ArrowLeft)),
+            (glfw::Key::Kp5, None),
+            (glfw::Key::Kp6, Some(egui::Key::ArrowRight)),
+            (glfw::Key::Kp7, Some(egui::Key::Home)),
+            (glfw::Key::Kp8, Some(egui::Key::ArrowUp)),
+            (glfw::Key::Kp9, Some(egui::Key::PageUp)),
+            (glfw::Key::KpDecimal, Some(egui::Key::Delete)),
+            (glfw::Key::KpDivide, Some(egui::Key::Slash)),
+            (glfw::Key::KpSubtract, Some(egui::Key::Minus)),
+            (glfw::Key::KpAdd, Some(egui::Key::Plus)),
+            (glfw::Key::KpEnter, Some(egui::Key::Enter)),
+            (glfw::Key::KpEqual, Some(egui::Key::Equals)),
+        ]
+        .iter()
+        .for_each(|(key, egui_key)| {
+            assert_eq!(
+                Input::get_key(key, &glfw::Modifiers::empty()),
+                *egui_key,
+                "{:?}",
+                key
+            );
+        });
+    }
+
+    #[test]
+    fn wheel_scroll_in_lines() {
+        let input = Input::new(1.0);
+        assert_eq!(
+            input.get_scroll_event(0.0, -1.0),
+            Event::MouseWheel {
+                unit: egui::MouseWheelUnit::Line,
+                delta: egui::vec2(0.0, -1.0),
+                modifiers: egui::Modifiers::default(),
+            }
+        );
+    }
+
+    #[test]
+    fn touchpad_scroll_in_points() {
+        let mut input = Input::new(1.0);
+        input.set_scroll_config(ScrollConfig {
+            multiplier: egui::vec2(-1.0, 2.0),
+            ..Default::default()
+        });
+        assert_eq!(
+            input.get_scroll_event(0.5, 0.25),
+            Event::MouseWheel {
+                unit: egui::MouseWheelUnit::Point,
+                delta: egui::vec2(-5.0, 5.0),
+                modifiers: egui::Modifiers::default(),
+            }
+        );
+    }
+
+    #[test]
+    fn scroll_without_shift_to_horizontal() {
+        let mut input = Input::new(1.0);
+        input.raw_input.modifiers = egui::Modifiers::SHIFT;
+        input.set_scroll_config(ScrollConfig {
+            shift_to_horizontal: false,
+            ..Default::default()
+        });
+        assert_eq!(
+            input.get_scroll_event(0.0, 1.0),
+            Event::MouseWheel {
+                unit: egui::MouseWheelUnit::Line,
+                delta: egui::vec2(0.0, 1.0),
+                modifiers: egui::Modifiers::default(),
+            }
+        );
+    }
+}
@@ -1,22 +1,75 @@
+mod atlas;
+mod clipboard;
 mod drawable;
+mod gpu_batch;
 mod gpu_immediate;
 mod input;
+#[cfg(feature = "replay")]
+mod replay;
+mod sampler;
+#[cfg(target_os = "linux")]
+mod scancode;
 mod shader;
 mod texture;
+mod texture_upload;
+mod url_opener;
 mod util;
+mod viewport;
 
-use std::{convert::TryInto, time::Duration, usize};
+use std::{
+    cell::RefCell,
+    convert::{TryFrom, TryInto},
+    rc::Rc,
+    time::Duration,
+};
 
+use atlas::TextureAtlas;
+pub use atlas::{ATLAS_MAX_IMAGE_SIZE, ATLAS_PAGE_SIZE};
+use clipboard::ClipboardIntegration;
+pub use clipboard::{Clipboard, ClipboardBindings, GlfwClipboard, MemoryClipboard};
 use drawable::Drawable;
-use gpu_immediate::{GPUImmediate, GPUVertCompType, GPUVertFetchMode};
+pub use gpu_batch::GPUBatch;
+pub use gpu_immediate::{
+    GPUImmediate, GPUIndexType, GPUPrimType, GPUVertCompType, GPUVertFetchMode, GPUVertFormat,
+    GPU_VERT_ATTR_MAX_LEN,
+};
 use input::Input;
-use shader::Shader;
-pub use texture::TextureRGBA8;
+pub use input::{CommandModifier, ScrollConfig};
+#[cfg(feature = "replay")]
+pub use replay::{
+    InputPlayer, InputRecorder, ReplayError, ReplayEvent, ReplayFrame, REPLAY_FORMAT_VERSION,
+};
+use sampler::SamplerCache;
+pub use shader::{Shader, ShaderError};
+pub use texture::{
+    Depth32F, GLTexture, PixelFormat, PixelRetention, Texture, TextureRGBA8, UserTexture, R8, RG8,
+    RGBA16F, RGBA32F, RGBA8,
+};
+pub use texture_upload::TextureUpload;
+use texture_upload::TextureUploadQueue;
+pub use url_opener::{RecordingUrlOpener, SystemUrlOpener, UrlOpener};
+use viewport::{ViewportWindow, Viewports, WindowCommandState};
 
 pub use egui;
-use egui::{epaint::ahash::AHashMap, ClippedPrimitive, PlatformOutput};
+use egui::{
+    epaint::ahash::{AHashMap, AHashSet},
+    ClippedPrimitive, PlatformOutput,
+};
+use glfw::Context;
 use nalgebra_glm as glm;
 
+/// Texture unit the egui textures are bound to while drawing the
+/// GUI. It is arbitrary, it just needs to be consistent between the
+/// shader, the texture and the sampler.
+const EGUI_TEXTURE_UNIT: gl::types::GLuint = 31;
+
+/// First id of the textures registered with the backend (see
+/// [`EguiBackend::register_texture()`]). Smaller ids of
+/// [`egui::TextureId::User`] that are not registered are treated as
+/// raw OpenGL texture names, which is how they were used before the
+/// textures could be registered.
+const USER_TEXTURE_ID_START: u64 = 1 << 32;
+
 /// Monitor data.
 #[derive(Debug)]
 pub struct MonitorData {
@@ -31,6 +84,20 @@ impl MonitorData {
     }
 }
 
+/// Statistics of the textures held by the [`EguiBackend`], see
+/// [`EguiBackend::get_texture_stats()`].
+#[derive(Debug, Clone, Default)]
+pub struct TextureStats {
+    /// Number of textures, both egui managed and user textures.
+    pub count: usize,
+    /// Number of bytes used by the textures on the GPU.
+    pub gpu_bytes: usize,
+    /// Number of bytes used by the CPU side copies of the textures.
+    pub cpu_bytes: usize,
+    /// Largest textures (by bytes on the GPU) in descending order.
+    pub largest: Vec<(egui::TextureId, usize)>,
+}
+
 /// Egui backend by which the GUI can be drawn, inputs are handled,
 /// etc.
 pub struct EguiBackend {
@@ -38,8 +105,43 @@ pub struct EguiBackend {
     input: Input,
     imm: GPUImmediate,
     textures: AHashMap<egui::TextureId, TextureRGBA8>,
+    user_textures: AHashMap<u64, (UserTexture, egui::TextureOptions)>,
+    next_user_texture_id: u64,
+    missing_texture: MissingTexture,
+    texture_atlas: TextureAtlas,
+    texture_upload_queue: TextureUploadQueue<egui::TextureId>,
+    samplers: SamplerCache,
+    /// Creation backtraces of the textures, [`Some`] only if the
+    /// texture debug mode is enabled.
+    texture_backtraces: Option<AHashMap<egui::TextureId, std::backtrace::Backtrace>>,
     shader: Shader,
     start_time: std::time::Instant,
+    /// Commands for the root viewport's window, see
+    /// [`EguiBackend::apply_viewport_commands()`].
+    viewport_commands: Vec<egui::ViewportCommand>,
+    /// State of the root window needed to apply the commands.
+    viewport_command_state: WindowCommandState,
+    /// Cursor icon of the last pass of the root viewport, applied
+    /// with the commands.
+    cursor_icon: egui::CursorIcon,
+    /// Clipboard for cut, copy and paste, see
+    /// [`EguiBackend::set_clipboard()`]. Shared with the immediate
+    /// viewport renderer.
+    clipboard: Rc<RefCell<ClipboardIntegration>>,
+    /// Opener of the URLs of [`egui::OutputCommand::OpenUrl`], see
+    /// [`EguiBackend::set_url_opener()`].
+    url_opener: Option<Box<dyn UrlOpener>>,
+    /// Recorder of the input, see
+    /// [`EguiBackend::set_input_recorder()`].
+    #[cfg(feature = "replay")]
+    input_recorder: Option<InputRecorder>,
+    /// Screen rect and time of the next pass set by
+    /// [`InputPlayer::play_frame()`].
+    #[cfg(feature = "replay")]
+    replay_pass: Option<(Option<egui::Rect>, f64)>,
+    /// Native windows of the viewports other than the root viewport,
+    /// see [`EguiBackend::enable_multi_viewport()`].
+    viewports: Rc<RefCell<Viewports>>,
 }
 
 /// Get the true pixels per point of the monitor that the window in
@@ -102,6 +204,9 @@ impl EguiBackend {
 
         let egui_ctx = egui::Context::default();
 
+        // the keypad keys depend on NumLock
+        window.set_store_lock_key_mods(true);
+
         // taking the x scale because egui supports only one value
         let pixels_per_point = window.get_content_scale().0;
         let mut input = Input::new(pixels_per_point);
@@ -122,15 +227,42 @@ impl EguiBackend {
             imm: GPUImmediate::new(),
             input,
             textures: AHashMap::new(),
+            user_textures: AHashMap::new(),
+            next_user_texture_id: USER_TEXTURE_ID_START,
+            missing_texture: MissingTexture::new(),
+            texture_atlas: TextureAtlas::new(),
+            texture_upload_queue: TextureUploadQueue::new(),
+            samplers: SamplerCache::new(),
+            texture_backtraces: None,
             shader,
             start_time: std::time::Instant::now(),
+            viewport_commands: Vec::new(),
+            viewport_command_state: WindowCommandState::default(),
+            cursor_icon: egui::CursorIcon::Default,
+            clipboard: Rc::new(RefCell::new(ClipboardIntegration::default())),
+            url_opener: Some(Box::new(SystemUrlOpener::new())),
+            #[cfg(feature = "replay")]
+            input_recorder: None,
+            #[cfg(feature = "replay")]
+            replay_pass: None,
+            viewports: Rc::new(RefCell::new(Viewports::default())),
         }
     }
 
     /// Start the egui frame. This sets up the necessary data for egui
-    /// to work.
-    pub fn begin_pass(&mut self, _window: &glfw::Window, _glfw: &mut glfw::Glfw) {
+    /// to work, including the [`egui::ViewportInfo`] of the `window`.
+    ///
+    /// Closing the window pushes [`egui::ViewportEvent::Close`], it
+    /// can be cancelled with [`egui::ViewportCommand::CancelClose`]
+    /// (see [`Self::apply_viewport_commands()`]).
+    pub fn begin_pass(&mut self, window: &glfw::Window, _glfw: &mut glfw::Glfw) {
         let time = self.start_time.elapsed().as_secs_f64();
+        #[cfg(feature = "replay")]
+        let replay_pass = self.replay_pass.take();
+        #[cfg(feature = "replay")]
+        let time = replay_pass.map_or(time, |(_, time)| time);
+        self.input
+            .update_viewport_info(window, self.get_window_pixels_per_point());
         // SAFETY: updating raw_input in a safe manner
         let raw_input = unsafe { self.get_raw_input() };
         let mut max_texture_size = 0;
@@ -140,7 +272,18 @@ impl EguiBackend {
         }
         raw_input.max_texture_side = Some(max_texture_size.try_into().unwrap());
         raw_input.time = Some(time);
-        self.egui_ctx.begin_pass(self.input.take());
+        #[cfg(feature = "replay")]
+        if let Some((screen_rect, _)) = replay_pass {
+            raw_input.screen_rect = screen_rect;
+        }
+        self.viewports.borrow_mut().time = time;
+        let mut raw_input = self.input.take();
+        #[cfg(feature = "replay")]
+        if let Some(recorder) = self.input_recorder.as_mut() {
+            recorder.record_frame(raw_input.screen_rect, time);
+        }
+        self.clipboard.borrow_mut().process_input(&mut raw_input);
+        self.egui_ctx.begin_pass(raw_input);
     }
 
     /// End the egui frame. This processes the GUI to render it to the
@@ -153,88 +296,410 @@ impl EguiBackend {
     ///
     /// # Note
     ///
-    /// It is up to the caller to handle the [`egui::PlatformOutput`]
-    /// within [`Output`], it is not processed by this function. This
-    /// allows for more flexibility in the implementation. But for
-    /// most use cases, it is useful to have clipboard support thus
-    /// see the provided example to handle the same.
+    /// Only the commands of the [`egui::PlatformOutput`] within
+    /// [`Output`] are processed by this function: the clipboard
+    /// commands when a clipboard is set (see
+    /// [`Self::set_clipboard()`]) and the URLs to open (see
+    /// [`Self::set_url_opener()`]). It is up to the caller to handle
+    /// the rest. This allows for more flexibility in the
+    /// implementation.
     ///
     /// # Example
     ///
-    /// The following example handles copying the required text to the
-    /// clipboard using the crate `copypasta_ext`.
-    ///
     /// ```no_run
-    /// let output = egui.end_pass((width as _, height as _));
+    /// egui.set_clipboard(Some(Box::new(egui_glfw::GlfwClipboard::new())));
     ///
-    /// output
-    ///     .platform_output
-    ///     .commands
-    ///     .into_iter()
-    ///     .filter_map(|output| match output {
-    ///         egui::OutputCommand::CopyText(text) => Some(text),
-    ///         egui::OutputCommand::CopyImage(_) | egui::OutputCommand::OpenUrl(_) => None,
-    ///     })
-    ///     .for_each(|text| match copypasta_ext::try_context() {
-    ///         Some(mut context) => context.set_contents(text).unwrap(),
-    ///         None => {
-    ///             eprintln!("enable to gather context for clipboard");
-    ///         }
-    ///     });
+    /// // the copied text is set in the clipboard
+    /// let output = egui.end_pass((width as _, height as _));
     /// ```
     pub fn end_pass(&mut self, screen_size_in_pixels: (f32, f32)) -> Output {
-        let full_output = self.egui_ctx.end_pass();
-
-        // TODO: need to handle full_output.textures_delta
-        //
-        // This will involve storing all the textures internally
-        // within Self.
+        let mut full_output = self.egui_ctx.end_pass();
+        let multi_viewport = !self.egui_ctx.embed_viewports();
 
-        // delete any textures that must be freed
-        full_output
-            .textures_delta
-            .free
-            .iter()
-            .for_each(|texture_id| {
-                self.textures.remove(texture_id);
-            });
-
-        // create or update textures
-        full_output
-            .textures_delta
-            .set
-            .iter()
-            .for_each(|(texture_id, delta)| {
-                if let Some(texture) = self.textures.get_mut(texture_id) {
-                    // update the texture
-                    texture.update_from_egui(delta);
-                } else {
-                    // create the texture
-                    self.textures
-                        .insert(*texture_id, TextureRGBA8::from_egui(delta).expect("new texture created but probably got a partial delta which doesn't make sense"));
-                }
-            });
+        // create or update textures, this must be done before
+        // drawing since the meshes may refer to them, the passes of
+        // the immediate viewports ran during this pass so their
+        // textures come first
+        let mut platform_output = PlatformOutput::default();
+        self.set_immediate_viewport_textures(&mut platform_output);
+        platform_output.append(full_output.platform_output);
+        self.set_textures(&full_output.textures_delta);
 
         #[cfg(feature = "tracing")]
-        if full_output.viewport_output.len() > 1 {
-            tracing::error!("multiple viewports are not supported");
+        if !multi_viewport && full_output.viewport_output.len() > 1 {
+            tracing::error!(
+                "multiple viewports are not enabled, see `EguiBackend::enable_multi_viewport()`"
+            );
         }
 
+        self.process_commands(&platform_output.commands);
+        self.cursor_icon = platform_output.cursor_icon;
+
         let output = Output {
-            platform_output: full_output.platform_output,
+            platform_output,
             repaint_after: full_output
                 .viewport_output
-                .into_values()
+                .get(&egui::ViewportId::ROOT)
                 .map(|viewport_output| viewport_output.repaint_delay)
-                .next()
                 .unwrap_or_default(),
         };
+        if let Some(viewport_output) = full_output.viewport_output.get_mut(&egui::ViewportId::ROOT)
+        {
+            self.viewport_commands.append(&mut viewport_output.commands);
+        }
+        if multi_viewport {
+            self.viewports.borrow_mut().viewport_outputs = full_output.viewport_output;
+        }
         let shapes = full_output.shapes;
+        let textures_delta = full_output.textures_delta;
 
-        let pixels_per_point = self.egui_ctx.pixels_per_point();
+        // upload this frame's share of the pending textures, on the
+        // texture unit of the egui textures so that the textures
+        // bound by the application are left alone
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0 + EGUI_TEXTURE_UNIT);
+        }
+        self.texture_upload_queue.process();
+
+        let pixels_per_point = full_output.pixels_per_point;
 
         let meshes = self.egui_ctx.tessellate(shapes, pixels_per_point);
 
+        self.paint(&meshes, pixels_per_point, screen_size_in_pixels);
+
+        // delete any textures that must be freed, this must be done
+        // after drawing since the meshes of this frame may still
+        // refer to them, with multiple viewports this is done once
+        // all the viewports are drawn
+        if multi_viewport {
+            self.viewports
+                .borrow_mut()
+                .textures_to_free
+                .extend(textures_delta.free);
+        } else {
+            self.free_textures(&textures_delta.free);
+        }
+
+        output
+    }
+
+    /// Apply the [`egui::ViewportCommand`]s for the root viewport
+    /// issued since the last call (for example, through
+    /// [`egui::Context::send_viewport_cmd()`]) to the root `window`.
+    /// Should be called after [`Self::end_pass()`].
+    ///
+    /// [`egui::ViewportCommand::Close`] requests the window to close
+    /// (see [`glfw::Window::should_close()`]) which can be cancelled
+    /// with [`egui::ViewportCommand::CancelClose`].
+    ///
+    /// The [`egui::CursorIcon`] of the last pass is applied too.
+    ///
+    /// Returns the commands that are not supported by GLFW.
+    pub fn apply_viewport_commands(
+        &mut self,
+        window: &mut glfw::Window,
+    ) -> Vec<egui::ViewportCommand> {
+        viewport::apply_cursor_icon(window, &mut self.viewport_command_state, self.cursor_icon);
+
+        let pixels_per_point = self.get_window_pixels_per_point();
+        std::mem::take(&mut self.viewport_commands)
+            .into_iter()
+            .filter_map(|command| match command {
+                egui::ViewportCommand::Close => {
+                    window.set_should_close(true);
+                    None
+                }
+                egui::ViewportCommand::RequestCut => {
+                    self.input.push_event(egui::Event::Cut);
+                    None
+                }
+                egui::ViewportCommand::RequestCopy => {
+                    self.input.push_event(egui::Event::Copy);
+                    None
+                }
+                egui::ViewportCommand::RequestPaste => {
+                    let paste_event = self.clipboard.borrow_mut().paste_event();
+                    if let Some(paste_event) = paste_event {
+                        self.input.push_event(paste_event);
+                        None
+                    } else {
+                        Some(egui::ViewportCommand::RequestPaste)
+                    }
+                }
+                command => viewport::apply_window_command(
+                    window,
+                    &mut self.viewport_command_state,
+                    pixels_per_point,
+                    command,
+                ),
+            })
+            .inspect(|_command| {
+                #[cfg(feature = "tracing")]
+                tracing::warn!("unsupported viewport command: {:?}", _command);
+            })
+            .collect()
+    }
+
+    /// Enable egui's multiple viewports. Viewports shown with
+    /// [`egui::Context::show_viewport_deferred()`] and
+    /// [`egui::Context::show_viewport_immediate()`] become native
+    /// GLFW windows that share the OpenGL context of the root window
+    /// instead of being embedded in the root window.
+    ///
+    /// [`Self::update_viewports()`] must be called after
+    /// [`Self::end_pass()`] every frame to create, update and draw
+    /// the windows of the viewports.
+    ///
+    /// Multiple backends on the same thread can enable multiple
+    /// viewports, the immediate viewports of each backend's context
+    /// are rendered by that backend.
+    pub fn enable_multi_viewport(&mut self) {
+        self.egui_ctx.set_embed_viewports(false);
+
+        viewport::register_immediate_viewport_renderer(
+            &self.egui_ctx,
+            Rc::downgrade(&self.viewports),
+            Rc::downgrade(&self.clipboard),
+        );
+    }
+
+    /// Create, update and draw the native windows of the viewports
+    /// other than the root viewport, requires
+    /// [`Self::enable_multi_viewport()`]. Must be called after
+    /// [`Self::end_pass()`] with the root `window`, whose OpenGL
+    /// context is current again after this.
+    ///
+    /// The input of the windows of the viewports is handled by the
+    /// backend. Returns the [`Output`] of the passes of the deferred
+    /// viewports.
+    pub fn update_viewports(&mut self, window: &mut glfw::Window) -> Output {
+        let mut output = Output {
+            platform_output: PlatformOutput::default(),
+            repaint_after: Duration::MAX,
+        };
+
+        let viewports = Rc::clone(&self.viewports);
+        let clipboard = Rc::clone(&self.clipboard);
+        let viewport_outputs = std::mem::take(&mut viewports.borrow_mut().viewport_outputs);
+
+        // destroy the windows of the viewports that are no longer
+        // shown, create or update the rest
+        viewports
+            .borrow_mut()
+            .windows
+            .retain(|viewport_id, _| viewport_outputs.contains_key(viewport_id));
+        // destroying a window leaves no context current
+        window.make_current();
+        viewport_outputs
+            .into_iter()
+            .filter(|(viewport_id, _)| *viewport_id != egui::ViewportId::ROOT)
+            .for_each(|(viewport_id, viewport_output)| {
+                output.repaint_after = output.repaint_after.min(viewport_output.repaint_delay);
+
+                let mut viewports = viewports.borrow_mut();
+                let recreate = match viewports.windows.get_mut(&viewport_id) {
+                    Some(viewport_window) => {
+                        viewport_window.patch_builder(viewport_output.builder.clone())
+                    }
+                    None => true,
+                };
+                if recreate {
+                    viewports.windows.remove(&viewport_id);
+                    if let Some(mut viewport_window) =
+                        ViewportWindow::new(window, viewport_id, viewport_output.builder)
+                    {
+                        viewport_window
+                            .input
+                            .set_command_modifier(viewports.command_modifier);
+                        viewport_window
+                            .input
+                            .set_scroll_config(viewports.scroll_config);
+                        viewports.windows.insert(viewport_id, viewport_window);
+                    } else {
+                        #[cfg(feature = "tracing")]
+                        tracing::error!("could not create window for {:?}", viewport_id);
+                    }
+                }
+
+                if let Some(viewport_window) = viewports.windows.get_mut(&viewport_id) {
+                    viewport_window.viewport_ui_cb = viewport_output.viewport_ui_cb;
+                    viewport_output.commands.into_iter().for_each(|command| {
+                        if let egui::ViewportCommand::RequestPaste = command {
+                            if let Some(paste_event) = clipboard.borrow_mut().paste_event() {
+                                viewport_window.input.push_event(paste_event);
+                                return;
+                            }
+                        }
+                        viewport_window.apply_command(command);
+                    });
+                }
+            });
+        // the passes use the root window's context, the windows'
+        // contexts are made current when they are drawn
+        window.make_current();
+
+        let clear_colour = egui::Rgba::from(self.egui_ctx.style().visuals.panel_fill);
+
+        // run the passes of the deferred viewports
+        let deferred_viewports: Vec<_> = viewports
+            .borrow()
+            .windows
+            .iter()
+            .filter_map(|(viewport_id, viewport_window)| {
+                viewport_window
+                    .viewport_ui_cb
+                    .clone()
+                    .map(|viewport_ui_cb| (*viewport_id, viewport_ui_cb))
+            })
+            .collect();
+        deferred_viewports
+            .into_iter()
+            .for_each(|(viewport_id, viewport_ui_cb)| {
+                let mut raw_input = viewports
+                    .borrow_mut()
+                    .take_raw_input(viewport_id, &Default::default());
+                clipboard.borrow_mut().process_input(&mut raw_input);
+                // the borrow must not be held while the pass runs
+                // since immediate viewports within need it
+                let full_output = self.egui_ctx.run(raw_input, |ctx| viewport_ui_cb(ctx));
+
+                self.set_immediate_viewport_textures(&mut output.platform_output);
+                self.set_textures(&full_output.textures_delta);
+                let cursor_icon = full_output.platform_output.cursor_icon;
+                output.platform_output.append(full_output.platform_output);
+
+                let pixels_per_point = full_output.pixels_per_point;
+                let primitives = self
+                    .egui_ctx
+                    .tessellate(full_output.shapes, pixels_per_point);
+
+                let mut viewports = viewports.borrow_mut();
+                viewports
+                    .textures_to_free
+                    .extend(full_output.textures_delta.free);
+                if let Some(viewport_window) = viewports.windows.get_mut(&viewport_id) {
+                    viewport_window.apply_cursor_icon(cursor_icon);
+                    viewport_window.pixels_per_point = pixels_per_point;
+                    viewport_window.paint(clear_colour, |viewport_window, screen_size| {
+                        self.paint_viewport(
+                            viewport_window,
+                            &primitives,
+                            pixels_per_point,
+                            screen_size,
+                        );
+                    });
+                }
+            });
+
+        // draw the immediate viewports, only their latest pass
+        let immediate_outputs = std::mem::take(&mut viewports.borrow_mut().immediate_outputs);
+        let mut viewports = viewports.borrow_mut();
+        let mut latest_immediate_outputs = egui::ViewportIdMap::default();
+        immediate_outputs.into_iter().for_each(|immediate_output| {
+            viewports
+                .textures_to_free
+                .extend(immediate_output.textures_delta.free.iter().copied());
+            latest_immediate_outputs.insert(immediate_output.viewport_id, immediate_output);
+        });
+        latest_immediate_outputs
+            .into_values()
+            .for_each(|immediate_output| {
+                if let Some(viewport_window) =
+                    viewports.windows.get_mut(&immediate_output.viewport_id)
+                {
+                    viewport_window.apply_cursor_icon(immediate_output.cursor_icon);
+                    viewport_window.pixels_per_point = immediate_output.pixels_per_point;
+                    viewport_window.paint(clear_colour, |viewport_window, screen_size| {
+                        self.paint_viewport(
+                            viewport_window,
+                            &immediate_output.primitives,
+                            immediate_output.pixels_per_point,
+                            screen_size,
+                        );
+                    });
+                }
+            });
+
+        // all viewports are drawn, the freed textures can be deleted
+        let textures_to_free = std::mem::take(&mut viewports.textures_to_free);
+        drop(viewports);
+        self.free_textures(&textures_to_free);
+
+        window.make_current();
+
+        self.process_commands(&output.platform_output.commands);
+
+        output
+    }
+
+    /// Process the [`egui::OutputCommand`]s of a pass, the clipboard
+    /// commands are processed with the clipboard and the URLs are
+    /// opened with the URL opener.
+    fn process_commands(&mut self, commands: &[egui::OutputCommand]) {
+        self.clipboard.borrow_mut().process_output(commands);
+        if let Some(url_opener) = self.url_opener.as_mut() {
+            commands.iter().for_each(|command| {
+                if let egui::OutputCommand::OpenUrl(open_url) = command {
+                    url_opener.open_url(open_url);
+                }
+            });
+        }
+    }
+
+    /// Set the textures of the passes of the immediate viewports
+    /// that are not set yet and append their platform output to
+    /// `platform_output`.
+    fn set_immediate_viewport_textures(&mut self, platform_output: &mut PlatformOutput) {
+        let viewports = Rc::clone(&self.viewports);
+        let mut viewports = viewports.borrow_mut();
+        viewports
+            .immediate_outputs
+            .iter_mut()
+            .for_each(|immediate_output| {
+                let set = std::mem::take(&mut immediate_output.textures_delta.set);
+                self.set_textures(&egui::TexturesDelta {
+                    set,
+                    free: Vec::new(),
+                });
+                platform_output.append(std::mem::take(&mut immediate_output.platform_output));
+            });
+    }
+
+    /// Create or update the textures of `textures_delta`.
+    fn set_textures(&mut self, textures_delta: &egui::TexturesDelta) {
+        textures_delta.set.iter().for_each(|(texture_id, delta)| {
+            if let Some(texture) = self.textures.get_mut(texture_id) {
+                // update the texture
+                texture.update_from_egui(delta);
+            } else if let Some(texture) = TextureRGBA8::from_egui(delta) {
+                // create the texture
+                self.textures.insert(*texture_id, texture);
+                self.texture_created(*texture_id);
+            } else {
+                #[cfg(feature = "tracing")]
+                tracing::error!(
+                    "got a partial delta for texture {:?} which does not exist",
+                    texture_id
+                );
+            }
+        });
+    }
+
+    /// Delete the textures freed by egui.
+    fn free_textures(&mut self, texture_ids: &[egui::TextureId]) {
+        texture_ids.iter().for_each(|texture_id| {
+            self.textures.remove(texture_id);
+            self.texture_freed(*texture_id);
+        });
+    }
+
+    /// Paint the tessellated `meshes` to the current framebuffer.
+    fn paint(
+        &mut self,
+        meshes: &[ClippedPrimitive],
+        pixels_per_point: f32,
+        screen_size_in_pixels: (f32, f32),
+    ) {
         self.shader.use_shader();
         let screen_size_in_points = glm::vec2(
             screen_size_in_pixels.0 / pixels_per_point,
@@ -243,12 +708,26 @@ impl EguiBackend {
         self.shader
             .set_vec2("u_screen_size_in_points\0", &screen_size_in_points);
         self.draw_gui(
-            &meshes,
+            meshes,
             pixels_per_point,
             glm::vec2(screen_size_in_pixels.0, screen_size_in_pixels.1),
         );
+    }
 
-        output
+    /// Paint the tessellated `meshes` to the window of a viewport,
+    /// the window's OpenGL context must be current.
+    fn paint_viewport(
+        &mut self,
+        viewport_window: &mut ViewportWindow,
+        meshes: &[ClippedPrimitive],
+        pixels_per_point: f32,
+        screen_size_in_pixels: (f32, f32),
+    ) {
+        // draw with the window's immediate mode drawing since vertex
+        // array objects are not shared between contexts
+        std::mem::swap(&mut self.imm, &mut viewport_window.imm);
+        self.paint(meshes, pixels_per_point, screen_size_in_pixels);
+        std::mem::swap(&mut self.imm, &mut viewport_window.imm);
     }
 
     /// Draw the gui by processing the provided `meshes`.
@@ -258,24 +737,327 @@ impl EguiBackend {
         pixels_per_point: f32,
         screen_size_in_pixels: glm::Vec2,
     ) {
-        // activate the texture. 31 is arbritrary, just needs to be
-        // consistent between the shader and the texture that is
-        // activated.
-        self.shader.set_int("u_texture\0", 31);
+        // activate the texture unit of the egui textures
+        self.shader
+            .set_int("u_texture\0", EGUI_TEXTURE_UNIT.try_into().unwrap());
         unsafe {
-            gl::ActiveTexture(gl::TEXTURE31);
+            gl::ActiveTexture(gl::TEXTURE0 + EGUI_TEXTURE_UNIT);
         }
 
         let mut draw_data = ClippedPrimitiveDrawData::new(
             &mut self.imm,
             &self.shader,
             &mut self.textures,
+            &mut self.user_textures,
+            &mut self.texture_atlas,
+            &mut self.missing_texture,
+            &mut self.samplers,
             pixels_per_point,
             screen_size_in_pixels,
         );
-        meshes
+
+        // merge consecutive meshes that are drawn with the same
+        // texture and clip rect (for example, images packed in the
+        // texture atlas) so that they are drawn together
+        let mut batches: Vec<MeshBatch> = Vec::new();
+        meshes.iter().for_each(|clipped_primitive| {
+            let mesh = match &clipped_primitive.primitive {
+                egui::epaint::Primitive::Mesh(mesh) => mesh,
+                egui::epaint::Primitive::Callback(_) => {
+                    todo!("Need to add support for callback primitive")
+                }
+            };
+            if mesh.indices.is_empty() {
+                return;
+            }
+
+            let (gl_tex, texture_options, uv_rect) = draw_data.resolve_texture(mesh.texture_id);
+            match batches.last_mut() {
+                Some(batch)
+                    if batch.gl_tex == gl_tex
+                        && batch.texture_options == texture_options
+                        && batch.clip_rect == clipped_primitive.clip_rect =>
+                {
+                    batch.meshes.push((mesh, uv_rect));
+                }
+                _ => batches.push(MeshBatch {
+                    clip_rect: clipped_primitive.clip_rect,
+                    gl_tex,
+                    texture_options,
+                    meshes: vec![(mesh, uv_rect)],
+                }),
+            }
+        });
+        batches
             .iter()
-            .for_each(|mesh| mesh.draw(&mut draw_data).unwrap_or(()));
+            .for_each(|batch| batch.draw(&mut draw_data).unwrap_or(()));
+
+        // do not leak the sampler to the rest of the application
+        SamplerCache::unbind(EGUI_TEXTURE_UNIT);
+    }
+
+    /// Register a texture with the backend so that it can be shown
+    /// by egui using the returned [`egui::TextureId`].
+    ///
+    /// The texture can be a [`Texture`] (of any [`PixelFormat`])
+    /// which is then owned by the backend, or a raw OpenGL texture
+    /// name whose lifetime is managed by the caller. The texture is
+    /// sampled with the given [`egui::TextureOptions`].
+    ///
+    /// The returned id is not the OpenGL texture name. A raw OpenGL
+    /// texture name can still be used directly as
+    /// `egui::TextureId::User(gl_tex as u64)` without registering it,
+    /// it is then sampled with its own parameters.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// let texture_id = egui.register_texture(texture, egui::TextureOptions::NEAREST);
+    ///
+    /// ui.image((texture_id, egui::vec2(width as _, height as _)));
+    /// ```
+    pub fn register_texture(
+        &mut self,
+        texture: impl Into<UserTexture>,
+        texture_options: egui::TextureOptions,
+    ) -> egui::TextureId {
+        let id = self.next_user_texture_id;
+        self.next_user_texture_id += 1;
+        self.user_textures
+            .insert(id, (texture.into(), texture_options));
+        self.texture_created(egui::TextureId::User(id));
+        egui::TextureId::User(id)
+    }
+
+    /// Register a texture similar to [`Self::register_texture()`]
+    /// but the pixels are uploaded to the GPU across frames (see
+    /// [`Self::set_texture_upload_budget()`]) so that large textures
+    /// do not stall the frame.
+    ///
+    /// Use [`Self::is_texture_pending()`] to check if the upload is
+    /// complete, for example to show a loading state until then. A
+    /// texture that is on the GPU already is registered as is.
+    pub fn register_texture_streamed<F: PixelFormat + 'static>(
+        &mut self,
+        mut texture: Texture<F>,
+        texture_options: egui::TextureOptions,
+    ) -> egui::TextureId {
+        let upload = texture.send_to_gpu_streamed();
+        let texture_id = self.register_texture(texture, texture_options);
+        if let Some(upload) = upload {
+            self.texture_upload_queue.push(texture_id, upload);
+        }
+        texture_id
+    }
+
+    /// Set the maximum number of bytes of the textures registered
+    /// with [`Self::register_texture_streamed()`] that are uploaded
+    /// per frame. At least one row of a texture is uploaded per frame
+    /// irrespective of the budget, so a budget of 0 uploads one row
+    /// per frame.
+    pub fn set_texture_upload_budget(&mut self, bytes_per_frame: usize) {
+        self.texture_upload_queue
+            .set_bytes_per_frame(bytes_per_frame);
+    }
+
+    /// Is the upload of the texture registered with
+    /// [`Self::register_texture_streamed()`] still pending.
+    pub fn is_texture_pending(&self, texture_id: egui::TextureId) -> bool {
+        self.texture_upload_queue.is_pending(texture_id)
+    }
+
+    /// Get the textures whose upload is still pending, see
+    /// [`Self::register_texture_streamed()`].
+    pub fn get_pending_textures(&self) -> Vec<egui::TextureId> {
+        self.texture_upload_queue.pending().collect()
+    }
+
+    /// Register a small image similar to [`Self::register_texture()`]
+    /// but pack it into a shared texture atlas when possible, so that
+    /// the meshes using the images in the atlas (for example, the
+    /// icons of a toolbar) are drawn with a handful of draw calls.
+    ///
+    /// Only images of at most [`ATLAS_MAX_IMAGE_SIZE`] that use
+    /// [`egui::TextureWrapMode::ClampToEdge`] without mipmaps are
+    /// packed, other images are registered as separate textures.
+    pub fn register_image_atlased(
+        &mut self,
+        image: &egui::ColorImage,
+        texture_options: egui::TextureOptions,
+    ) -> egui::TextureId {
+        if !TextureAtlas::supports(image.size, &texture_options) {
+            return self.register_texture(
+                TextureRGBA8::from_color_image(image, texture_options),
+                texture_options,
+            );
+        }
+
+        let id = self.next_user_texture_id;
+        self.next_user_texture_id += 1;
+        self.texture_atlas.insert(id, image, texture_options);
+        self.texture_created(egui::TextureId::User(id));
+        egui::TextureId::User(id)
+    }
+
+    /// Replace the texture registered as `texture_id` (see
+    /// [`Self::register_texture()`]), returns the previous texture.
+    ///
+    /// Returns [`None`] and does nothing if `texture_id` is not a
+    /// registered texture. Images packed in the texture atlas (see
+    /// [`Self::register_image_atlased()`]) are removed from the
+    /// atlas and [`None`] is returned.
+    pub fn update_texture(
+        &mut self,
+        texture_id: egui::TextureId,
+        texture: impl Into<UserTexture>,
+        texture_options: egui::TextureOptions,
+    ) -> Option<UserTexture> {
+        let egui::TextureId::User(id) = texture_id else {
+            return None;
+        };
+        self.texture_upload_queue.cancel(texture_id);
+        if self.texture_atlas.remove(id) {
+            self.user_textures
+                .insert(id, (texture.into(), texture_options));
+            self.texture_created(texture_id);
+            return None;
+        }
+        let previous = self.user_textures.get_mut(&id).map(|user_texture| {
+            std::mem::replace(user_texture, (texture.into(), texture_options)).0
+        });
+        if previous.is_some() {
+            self.texture_created(texture_id);
+        }
+        previous
+    }
+
+    /// Free the texture registered as `texture_id` (see
+    /// [`Self::register_texture()`]). The texture is returned so
+    /// that, for example, raw OpenGL textures can be deleted by the
+    /// caller, dropping an owned texture deletes it.
+    ///
+    /// Returns [`None`] if `texture_id` is not a registered texture
+    /// or is an image packed in the texture atlas (see
+    /// [`Self::register_image_atlased()`]).
+    pub fn free_texture(&mut self, texture_id: egui::TextureId) -> Option<UserTexture> {
+        let egui::TextureId::User(id) = texture_id else {
+            return None;
+        };
+        self.texture_upload_queue.cancel(texture_id);
+        self.texture_freed(texture_id);
+        if self.texture_atlas.remove(id) {
+            return None;
+        }
+        self.user_textures
+            .remove(&id)
+            .map(|(user_texture, _)| user_texture)
+    }
+
+    /// Get the [`TextureStats`] of the textures currently held by
+    /// the backend, listing at most `largest_len` of the largest
+    /// textures. The pages of the texture atlas are counted but not
+    /// listed.
+    ///
+    /// Combined with [`Self::get_texture_backtrace()`], this can be
+    /// used to find where leaked textures were created.
+    pub fn get_texture_stats(&self, largest_len: usize) -> TextureStats {
+        let mut stats = TextureStats::default();
+        let mut sizes = Vec::with_capacity(self.textures.len() + self.user_textures.len());
+
+        self.textures.iter().for_each(|(texture_id, texture)| {
+            stats.gpu_bytes += texture.get_gpu_bytes();
+            stats.cpu_bytes += texture.get_cpu_bytes();
+            sizes.push((*texture_id, texture.get_gpu_bytes()));
+        });
+        self.user_textures.iter().for_each(|(id, (texture, _))| {
+            stats.gpu_bytes += texture.get_gpu_bytes();
+            stats.cpu_bytes += texture.get_cpu_bytes();
+            sizes.push((egui::TextureId::User(*id), texture.get_gpu_bytes()));
+        });
+
+        stats.gpu_bytes += self.texture_atlas.get_gpu_bytes();
+        stats.cpu_bytes += self.texture_atlas.get_cpu_bytes();
+
+        stats.count = sizes.len() + self.texture_atlas.get_page_len();
+        sizes.sort_unstable_by(|(_, a), (_, b)| b.cmp(a));
+        sizes.truncate(largest_len);
+        stats.largest = sizes;
+
+        stats
+    }
+
+    /// Enable or disable the texture debug mode.
+    ///
+    /// When enabled, the backtrace of where each texture is created
+    /// is recorded (see [`Self::get_texture_backtrace()`]) and the
+    /// OpenGL textures are labelled with their [`egui::TextureId`]
+    /// (through `glObjectLabel`, if supported) so that they can be
+    /// identified in graphics debuggers. Only textures created
+    /// while the debug mode is enabled are tracked.
+    ///
+    /// Labelling uploads the textures to the GPU on creation.
+    pub fn set_texture_debug(&mut self, enable: bool) {
+        if enable {
+            self.texture_backtraces.get_or_insert_with(AHashMap::new);
+        } else {
+            self.texture_backtraces = None;
+        }
+    }
+
+    /// Get the backtrace of where the texture `texture_id` was
+    /// created, requires the texture debug mode (see
+    /// [`Self::set_texture_debug()`]).
+    pub fn get_texture_backtrace(
+        &self,
+        texture_id: egui::TextureId,
+    ) -> Option<&std::backtrace::Backtrace> {
+        self.texture_backtraces.as_ref()?.get(&texture_id)
+    }
+
+    /// Record the creation of the texture `texture_id` if the
+    /// texture debug mode is enabled.
+    fn texture_created(&mut self, texture_id: egui::TextureId) {
+        self.missing_texture.forget(texture_id);
+
+        let Some(texture_backtraces) = self.texture_backtraces.as_mut() else {
+            return;
+        };
+        texture_backtraces.insert(texture_id, std::backtrace::Backtrace::force_capture());
+
+        let gl_tex = match texture_id {
+            egui::TextureId::Managed(_) => self
+                .textures
+                .get_mut(&texture_id)
+                .map(|texture| texture.get_gl_tex()),
+            egui::TextureId::User(id) => self
+                .user_textures
+                .get_mut(&id)
+                .and_then(|(texture, _)| texture.get_gl_tex()),
+        };
+        if let Some(gl_tex) = gl_tex {
+            util::set_gl_object_label(gl::TEXTURE, gl_tex, &format!("egui {:?}", texture_id));
+        }
+    }
+
+    /// Forget the texture `texture_id` if the texture debug mode is
+    /// enabled.
+    fn texture_freed(&mut self, texture_id: egui::TextureId) {
+        self.missing_texture.forget(texture_id);
+        if let Some(texture_backtraces) = self.texture_backtraces.as_mut() {
+            texture_backtraces.remove(&texture_id);
+        }
+    }
+
+    /// Set the placeholder texture used to draw meshes that refer
+    /// to a texture that does not exist (freed or never set) or is
+    /// not a valid OpenGL texture. Defaults to a magenta and black
+    /// checkerboard.
+    pub fn set_missing_texture_placeholder(
+        &mut self,
+        texture: impl Into<UserTexture>,
+        texture_options: egui::TextureOptions,
+    ) {
+        self.missing_texture.placeholder = (texture.into(), texture_options);
     }
 
     /// Process the [`glfw::WindowEvent`] to convert it to an event
@@ -283,9 +1065,146 @@ impl EguiBackend {
     ///
     /// Also look at [`Self::push_event()`] for handling other egui
     /// events that are currently unsupported.
-    pub fn handle_event(&mut self, event: &glfw::WindowEvent, window: &glfw::Window) {
+    ///
+    /// Returns whether egui consumed the event, as per the state of
+    /// egui at the end of the last pass, so that the application can
+    /// ignore the events that are meant for egui.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// if !egui.handle_event(&event, &window).consumed {
+    ///     camera.handle_event(&event);
+    /// }
+    /// ```
+    pub fn handle_event(
+        &mut self,
+        event: &glfw::WindowEvent,
+        window: &glfw::Window,
+    ) -> EventResponse {
+        #[cfg(feature = "replay")]
+        if let Some(recorder) = self.input_recorder.as_mut() {
+            // the replay must not depend on the cursor of the window
+            if let Some(cursor_pos) = self.input.get_missing_cursor_pos(event, window) {
+                recorder.record_window_event(&cursor_pos);
+            }
+            recorder.record_window_event(event);
+        }
         self.input
-            .handle_event(event, window, self.egui_ctx.pixels_per_point());
+            .handle_event(event, window, self.get_window_pixels_per_point());
+
+        let ctx = &self.egui_ctx;
+        let wants_pointer = || ctx.wants_pointer_input() || ctx.is_pointer_over_area();
+        match event {
+            glfw::WindowEvent::CursorPos(_, _) => EventResponse {
+                // only a drag that started in egui is meant for egui
+                consumed: ctx.is_using_pointer(),
+                repaint: true,
+            },
+            glfw::WindowEvent::MouseButton(_, _, _)
+            | glfw::WindowEvent::Scroll(_, _)
+            | glfw::WindowEvent::FileDrop(_) => EventResponse {
+                consumed: wants_pointer(),
+                repaint: true,
+            },
+            // `wants_keyboard_input()` is set while a text edit has
+            // focus
+            glfw::WindowEvent::Key(_, _, _, _) | glfw::WindowEvent::Char(_) => EventResponse {
+                consumed: ctx.wants_keyboard_input(),
+                repaint: true,
+            },
+            glfw::WindowEvent::CharModifiers(_, _) => EventResponse {
+                consumed: ctx.wants_keyboard_input(),
+                repaint: false,
+            },
+            // window events concern the application too
+            glfw::WindowEvent::Pos(_, _)
+            | glfw::WindowEvent::Size(_, _)
+            | glfw::WindowEvent::Close
+            | glfw::WindowEvent::Refresh
+            | glfw::WindowEvent::Focus(_)
+            | glfw::WindowEvent::Iconify(_)
+            | glfw::WindowEvent::FramebufferSize(_, _)
+            | glfw::WindowEvent::CursorEnter(_)
+            | glfw::WindowEvent::Maximize(_)
+            | glfw::WindowEvent::ContentScale(_, _) => EventResponse {
+                consumed: false,
+                repaint: true,
+            },
+        }
+    }
+
+    /// Set the modifier key used for [`egui::Modifiers::command`],
+    /// that is the modifier of shortcuts such as copy. Defaults to
+    /// super on macOS and control elsewhere.
+    pub fn set_command_modifier(&mut self, command_modifier: CommandModifier) {
+        self.input.set_command_modifier(command_modifier);
+        let mut viewports = self.viewports.borrow_mut();
+        viewports.command_modifier = command_modifier;
+        viewports.windows.values_mut().for_each(|viewport_window| {
+            viewport_window.input.set_command_modifier(command_modifier)
+        });
+    }
+
+    /// Get the modifier key used for [`egui::Modifiers::command`].
+    pub fn get_command_modifier(&self) -> CommandModifier {
+        self.input.get_command_modifier()
+    }
+
+    /// Set how the scroll deltas of GLFW are sent to egui, see
+    /// [`ScrollConfig`].
+    pub fn set_scroll_config(&mut self, scroll_config: ScrollConfig) {
+        self.input.set_scroll_config(scroll_config);
+        let mut viewports = self.viewports.borrow_mut();
+        viewports.scroll_config = scroll_config;
+        viewports
+            .windows
+            .values_mut()
+            .for_each(|viewport_window| viewport_window.input.set_scroll_config(scroll_config));
+    }
+
+    /// Get how the scroll deltas of GLFW are sent to egui.
+    pub fn get_scroll_config(&self) -> ScrollConfig {
+        self.input.get_scroll_config()
+    }
+
+    /// Set the [`Clipboard`] used for cut, copy and paste, they are
+    /// not handled when [`None`] (the default).
+    ///
+    /// With a clipboard, the key presses that match the
+    /// [`ClipboardBindings`] push [`egui::Event::Cut`],
+    /// [`egui::Event::Copy`] and [`egui::Event::Paste`], and the
+    /// copied text (and images if supported) are set in the
+    /// clipboard by [`Self::end_pass()`] and
+    /// [`Self::update_viewports()`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// egui.set_clipboard(Some(Box::new(egui_glfw::GlfwClipboard::new())));
+    /// ```
+    pub fn set_clipboard(&mut self, clipboard: Option<Box<dyn Clipboard>>) {
+        self.clipboard.borrow_mut().clipboard = clipboard;
+    }
+
+    /// Set the keyboard shortcuts for cut, copy and paste, see
+    /// [`Self::set_clipboard()`].
+    pub fn set_clipboard_bindings(&mut self, bindings: ClipboardBindings) {
+        self.clipboard.borrow_mut().bindings = bindings;
+    }
+
+    /// Get the keyboard shortcuts for cut, copy and paste.
+    pub fn get_clipboard_bindings(&self) -> ClipboardBindings {
+        self.clipboard.borrow().bindings.clone()
+    }
+
+    /// Set the [`UrlOpener`] used to open the URLs of
+    /// [`egui::OutputCommand::OpenUrl`] (for example, clicked
+    /// hyperlinks) in [`Self::end_pass()`] and
+    /// [`Self::update_viewports()`]. Defaults to [`SystemUrlOpener`],
+    /// URLs are not opened when [`None`].
+    pub fn set_url_opener(&mut self, url_opener: Option<Box<dyn UrlOpener>>) {
+        self.url_opener = url_opener;
     }
 
     /// Push a [`egui::Event`] to egui. This is useful when a certain
@@ -296,58 +1215,61 @@ impl EguiBackend {
     ///
     /// # Example
     ///
-    /// A common use case would be to setup the code for cut, copy,
-    /// paste since it is not handled by `egui_glfw` due to shortcut
-    /// discrepancies.
+    /// Cut, copy and paste are handled by the backend with a
+    /// clipboard (see [`Self::set_clipboard()`]), they can also be
+    /// pushed for shortcuts other than the [`ClipboardBindings`].
     ///
     /// ```no_run
     /// egui.handle_event(event, window);
     /// match event {
-    ///     glfw::WindowEvent::Key(
-    ///         glfw::Key::X,
-    ///         _,
-    ///         glfw::Action::Press,
-    ///         glfw::Modifiers::Control,
-    ///     ) => {
-    ///         egui.push_event(egui::Event::Cut);
-    ///     }
-    ///     glfw::WindowEvent::Key(
-    ///         glfw::Key::C,
-    ///         _,
-    ///         glfw::Action::Press,
-    ///         glfw::Modifiers::Control,
-    ///     ) => {
+    ///     glfw::WindowEvent::Key(glfw::Key::F2, _, glfw::Action::Press, _) => {
     ///         egui.push_event(egui::Event::Copy);
     ///     }
-    ///     glfw::WindowEvent::Key(
-    ///         glfw::Key::V,
-    ///         _,
-    ///         glfw::Action::Press,
-    ///         glfw::Modifiers::Control,
-    ///     ) => {
-    ///         let text = match copypasta_ext::try_context() {
-    ///             Some(mut context) => Some(context.get_contents().unwrap()),
-    ///             None => {
-    ///                 eprintln!("enable to gather context for clipboard");
-    ///                 None
-    ///             }
-    ///         };
-    ///         if let Some(text) = text {
-    ///             egui.push_event(egui::Event::Text(text));
-    ///         }
-    ///     }
     ///     _ => {}
     /// }
     /// ```
     ///
     /// Note that for [`egui::Event::Cut`] and [`egui::Event::Copy`],
-    /// it is important support handle the output so that the copied
-    /// text is copied to the clipboard. See [`Self::end_pass()`] for
-    /// more details.
+    /// the copied text is set in the clipboard only if one is set,
+    /// see [`Self::end_pass()`] for more details.
     pub fn push_event(&mut self, event: egui::Event) {
+        #[cfg(feature = "replay")]
+        if let Some(recorder) = self.input_recorder.as_mut() {
+            recorder.record_egui_event(&event);
+        }
         self.input.push_event(event);
     }
 
+    /// Set the [`InputRecorder`] that records the events given to
+    /// [`Self::handle_event()`] and [`Self::push_event()`] along with
+    /// the frames begun by [`Self::begin_pass()`], to replay them
+    /// with [`InputPlayer`]. Recording stops when [`None`].
+    ///
+    /// Only the input of the root viewport is recorded.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// egui.set_input_recorder(Some(egui_glfw::InputRecorder::create("bug.replay")?));
+    /// ```
+    #[cfg(feature = "replay")]
+    pub fn set_input_recorder(&mut self, input_recorder: Option<InputRecorder>) {
+        self.input_recorder = input_recorder;
+    }
+
+    /// Take the [`InputRecorder`], this stops recording.
+    #[cfg(feature = "replay")]
+    pub fn take_input_recorder(&mut self) -> Option<InputRecorder> {
+        self.input_recorder.take()
+    }
+
+    /// Make the next [`Self::begin_pass()`] use `screen_rect` and
+    /// `time`, see [`InputPlayer::play_frame()`].
+    #[cfg(feature = "replay")]
+    fn set_replay_pass(&mut self, screen_rect: Option<egui::Rect>, time: f64) {
+        self.replay_pass = Some((screen_rect, time));
+    }
+
     /// Get the internal raw input state mutably.
     ///
     /// # Safety
@@ -364,6 +1286,16 @@ impl EguiBackend {
     pub fn get_egui_ctx(&self) -> &egui::Context {
         &self.egui_ctx
     }
+
+    /// Get the pixels per point to convert the positions and sizes
+    /// of the window to points with, the native pixels per point
+    /// (the content scale of the window) scaled by egui's zoom
+    /// factor. Unlike [`egui::Context::pixels_per_point()`], it is
+    /// up to date as soon as the content scale changes rather than
+    /// from the next pass.
+    fn get_window_pixels_per_point(&self) -> f32 {
+        self.egui_ctx.zoom_factor() * self.input.get_native_pixels_per_point()
+    }
 }
 
 struct ClippedPrimitiveDrawData<'a> {
@@ -374,6 +1306,14 @@ struct ClippedPrimitiveDrawData<'a> {
     shader: &'a Shader,
     /// Textures used by egui.
     textures: &'a mut AHashMap<egui::TextureId, TextureRGBA8>,
+    /// Textures registered by the user.
+    user_textures: &'a mut AHashMap<u64, (UserTexture, egui::TextureOptions)>,
+    /// Atlas of the small images registered by the user.
+    texture_atlas: &'a mut TextureAtlas,
+    /// Placeholder for textures that are missing.
+    missing_texture: &'a mut MissingTexture,
+    /// Samplers for the textures used by egui.
+    samplers: &'a mut SamplerCache,
 
     /// Pixels per point.
     pixels_per_point: f32,
@@ -383,10 +1323,15 @@ struct ClippedPrimitiveDrawData<'a> {
 }
 
 impl<'a> ClippedPrimitiveDrawData<'a> {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         imm: &'a mut GPUImmediate,
         shader: &'a Shader,
         textures: &'a mut AHashMap<egui::TextureId, TextureRGBA8>,
+        user_textures: &'a mut AHashMap<u64, (UserTexture, egui::TextureOptions)>,
+        texture_atlas: &'a mut TextureAtlas,
+        missing_texture: &'a mut MissingTexture,
+        samplers: &'a mut SamplerCache,
         pixels_per_point: f32,
         screen_size_in_pixels: glm::Vec2,
     ) -> Self {
@@ -394,38 +1339,122 @@ impl<'a> ClippedPrimitiveDrawData<'a> {
             imm,
             shader,
             textures,
+            user_textures,
+            texture_atlas,
+            missing_texture,
+            samplers,
             pixels_per_point,
             screen_size_in_pixels,
         }
     }
-}
 
-impl Drawable<ClippedPrimitiveDrawData<'_>, ()> for ClippedPrimitive {
-    fn draw(&self, extra_data: &mut ClippedPrimitiveDrawData) -> Result<(), ()> {
-        let rect = &self.clip_rect;
-        let mesh = match &self.primitive {
-            egui::epaint::Primitive::Mesh(mesh) => mesh,
-            egui::epaint::Primitive::Callback(_) => {
-                todo!("Need to add support for callback primitive")
-            }
+    /// Get the OpenGL texture name and [`egui::TextureOptions`] to
+    /// draw the mesh using `texture_id` with, along with the UV rect
+    /// of the image within the texture if it is packed in the
+    /// texture atlas. The options are [`None`] for raw OpenGL
+    /// textures that are not registered, they are sampled with their
+    /// own parameters. Falls back to the placeholder if the texture
+    /// is missing.
+    fn resolve_texture(
+        &mut self,
+        texture_id: egui::TextureId,
+    ) -> (
+        gl::types::GLuint,
+        Option<egui::TextureOptions>,
+        Option<egui::Rect>,
+    ) {
+        let texture = match texture_id {
+            egui::TextureId::Managed(_) => self
+                .textures
+                .get_mut(&texture_id)
+                .map(|texture| {
+                    (
+                        texture.get_gl_tex(),
+                        Some(*texture.get_texture_options()),
+                        None,
+                    )
+                })
+                .ok_or("texture does not exist"),
+            egui::TextureId::User(id) => match self.texture_atlas.get_gl_tex(id) {
+                Some((gl_tex, texture_options, uv_rect)) => {
+                    Ok((gl_tex, Some(texture_options), Some(uv_rect)))
+                }
+                None => {
+                    get_user_texture_options(self.user_textures, id).and_then(|texture_options| {
+                        let gl_tex = match self.user_textures.get_mut(&id) {
+                            Some((user_texture, _)) => user_texture.get_gl_tex(),
+                            None => UserTexture::Raw(id.try_into().unwrap()).get_gl_tex(),
+                        };
+                        gl_tex
+                            .map(|gl_tex| (gl_tex, texture_options, None))
+                            .ok_or("user texture is not a valid OpenGL texture")
+                    })
+                }
+            },
         };
-
-        match mesh.texture_id {
-            egui::TextureId::Managed(_) => {
-                let texture = &mut extra_data.textures.get_mut(&mesh.texture_id).unwrap();
-                unsafe {
-                    gl::BindTexture(gl::TEXTURE_2D, texture.get_gl_tex());
+        match texture {
+            Ok(texture) => texture,
+            Err(_reason) => {
+                #[cfg(feature = "tracing")]
+                if self.missing_texture.reported.insert(texture_id) {
+                    tracing::warn!(
+                        "{:?}: {}, drawing with the placeholder texture",
+                        texture_id,
+                        _reason
+                    );
                 }
+                let (gl_tex, texture_options) = self
+                    .missing_texture
+                    .get_gl_tex()
+                    .expect("placeholder texture must be a valid OpenGL texture");
+                (gl_tex, Some(texture_options), None)
             }
-            egui::TextureId::User(gl_tex) => unsafe {
-                gl::BindTexture(gl::TEXTURE_2D, gl_tex.try_into().unwrap());
-            },
         }
+    }
+}
+
+/// Get the [`egui::TextureOptions`] the user texture `id` is sampled
+/// with. Raw OpenGL texture names that are not registered are
+/// sampled with their own parameters, for which [`None`] is
+/// returned.
+fn get_user_texture_options(
+    user_textures: &AHashMap<u64, (UserTexture, egui::TextureOptions)>,
+    id: u64,
+) -> Result<Option<egui::TextureOptions>, &'static str> {
+    match user_textures.get(&id) {
+        Some((_, texture_options)) => Ok(Some(*texture_options)),
+        None if id < USER_TEXTURE_ID_START => Ok(None),
+        None => Err("user texture is not registered"),
+    }
+}
 
-        if mesh.indices.is_empty() {
-            // TODO(ish): make this a proper error
-            return Err(()); // mesh is not a mesh, no indices
+/// Consecutive meshes that are drawn with the same texture and clip
+/// rect in a single draw call.
+struct MeshBatch<'a> {
+    /// Clip rect of the meshes.
+    clip_rect: egui::Rect,
+    /// OpenGL texture the meshes are drawn with.
+    gl_tex: gl::types::GLuint,
+    /// [`egui::TextureOptions`] the texture is sampled with, [`None`]
+    /// to sample with the parameters of the texture itself.
+    texture_options: Option<egui::TextureOptions>,
+    /// Meshes along with the UV rect their UVs are mapped to, if
+    /// any.
+    meshes: Vec<(&'a egui::Mesh, Option<egui::Rect>)>,
+}
+
+impl Drawable<ClippedPrimitiveDrawData<'_>, ()> for MeshBatch<'_> {
+    fn draw(&self, extra_data: &mut ClippedPrimitiveDrawData) -> Result<(), ()> {
+        let rect = &self.clip_rect;
+
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_2D, self.gl_tex);
+        }
+        match &self.texture_options {
+            Some(texture_options) => extra_data.samplers.bind(EGUI_TEXTURE_UNIT, texture_options),
+            None => SamplerCache::unbind(EGUI_TEXTURE_UNIT),
         }
+
         let imm = &mut extra_data.imm;
         let shader = extra_data.shader;
         shader.use_shader();
@@ -495,24 +1524,46 @@ impl Drawable<ClippedPrimitiveDrawData<'_>, ()> for ClippedPrimitive {
             );
         }
 
-        imm.begin(gpu_immediate::GPUPrimType::Tris, mesh.indices.len(), shader);
+        imm.begin_indexed(
+            gpu_immediate::GPUPrimType::Tris,
+            self.meshes
+                .iter()
+                .map(|(mesh, _)| mesh.vertices.len())
+                .sum(),
+            GPUIndexType::U32,
+            self.meshes.iter().map(|(mesh, _)| mesh.indices.len()).sum(),
+            shader,
+        );
 
-        mesh.indices.iter().for_each(|index| {
-            let vert = &mesh.vertices[*index as usize];
+        self.meshes.iter().for_each(|(mesh, uv_rect)| {
+            mesh.vertices.iter().for_each(|vert| {
+                // map the UV to the image's region of the texture
+                let uv = uv_rect.map_or(vert.uv, |uv_rect| {
+                    uv_rect.min + vert.uv.to_vec2() * uv_rect.size()
+                });
+                // need to flip the y coordinate of the UV since egui
+                // has (0.0, 0.0) as top left and (1.0, 1.0) as bottom
+                // right but OpenGL has (0.0, 0.0) as bottom left and
+                // (1.0, 1.0) as top right
+                imm.attr_2f(uv_attr, uv.x, 1.0 - uv.y);
+                imm.attr_4f(
+                    colour_attr,
+                    vert.color.r().into(),
+                    vert.color.g().into(),
+                    vert.color.b().into(),
+                    vert.color.a().into(),
+                );
+                imm.vertex_2f(pos_attr, vert.pos.x, vert.pos.y);
+            });
+        });
 
-            // need to flip the y coordinate of the UV since egui has
-            // (0.0, 0.0) as top left and (1.0, 1.0) as bottom right
-            // but OpenGL has (0.0, 0.0) as bottom left and (1.0, 1.0)
-            // as top right
-            imm.attr_2f(uv_attr, vert.uv.x, 1.0 - vert.uv.y);
-            imm.attr_4f(
-                colour_attr,
-                vert.color.r().into(),
-                vert.color.g().into(),
-                vert.color.b().into(),
-                vert.color.a().into(),
-            );
-            imm.vertex_2f(pos_attr, vert.pos.x, vert.pos.y);
+        // the indices of each mesh are relative to its own vertices
+        let mut base_vertex = 0;
+        self.meshes.iter().for_each(|(mesh, _)| {
+            mesh.indices
+                .iter()
+                .for_each(|index| imm.index(base_vertex + *index));
+            base_vertex += u32::try_from(mesh.vertices.len()).unwrap();
         });
 
         imm.end();
@@ -547,6 +1598,58 @@ impl Drawable<ClippedPrimitiveDrawData<'_>, ()> for ClippedPrimitive {
     }
 }
 
+/// Placeholder used to draw meshes whose texture is missing.
+struct MissingTexture {
+    /// Placeholder texture.
+    placeholder: (UserTexture, egui::TextureOptions),
+    /// Textures that were reported missing already, to not report
+    /// them every frame.
+    reported: AHashSet<egui::TextureId>,
+}
+
+impl MissingTexture {
+    /// Create a new [`MissingTexture`] with a magenta and black
+    /// checkerboard as the placeholder.
+    fn new() -> Self {
+        const SIZE: usize = 16;
+        const CELL_SIZE: usize = 4;
+        let pixels = (0..(SIZE * SIZE))
+            .map(|pixel| {
+                let (x, y) = (pixel % SIZE, pixel / SIZE);
+                if ((x / CELL_SIZE) ^ (y / CELL_SIZE)) & 1 == 0 {
+                    (255, 0, 255, 255)
+                } else {
+                    (0, 0, 0, 255)
+                }
+            })
+            .collect();
+
+        Self {
+            placeholder: (
+                TextureRGBA8::from_pixels(SIZE, SIZE, pixels, egui::TextureOptions::NEAREST).into(),
+                egui::TextureOptions::NEAREST,
+            ),
+            reported: AHashSet::new(),
+        }
+    }
+
+    /// Get the OpenGL texture name and [`egui::TextureOptions`] of
+    /// the placeholder.
+    fn get_gl_tex(&mut self) -> Option<(gl::types::GLuint, egui::TextureOptions)> {
+        let (texture, texture_options) = &mut self.placeholder;
+        texture
+            .get_gl_tex()
+            .map(|gl_tex| (gl_tex, *texture_options))
+    }
+
+    /// Forget that the texture `texture_id` was reported missing, so
+    /// that it is reported again if it goes missing after being
+    /// created or freed.
+    fn forget(&mut self, texture_id: egui::TextureId) {
+        self.reported.remove(&texture_id);
+    }
+}
+
 /// Output of [`EguiBackend::end_pass()`].
 pub struct Output {
     /// egui's [`PlatformOutput`].
@@ -556,3 +1659,37 @@ pub struct Output {
     /// more details.
     pub repaint_after: Duration,
 }
+
+/// Response of [`EguiBackend::handle_event()`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EventResponse {
+    /// Is the event meant for egui, if so the application should not
+    /// handle it (for example, a click on a window or a key press in
+    /// a text edit).
+    pub consumed: bool,
+    /// Should egui be repainted due to the event.
+    pub repaint: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn raw_user_texture_has_no_sampler_options() {
+        let mut user_textures = AHashMap::new();
+        let texture_options = egui::TextureOptions::NEAREST_REPEAT;
+        user_textures.insert(
+            USER_TEXTURE_ID_START,
+            (UserTexture::Raw(7), texture_options),
+        );
+
+        // raw OpenGL texture names keep their own parameters
+        assert_eq!(get_user_texture_options(&user_textures, 7), Ok(None));
+        assert_eq!(
+            get_user_texture_options(&user_textures, USER_TEXTURE_ID_START),
+            Ok(Some(texture_options))
+        );
+        assert!(get_user_texture_options(&user_textures, USER_TEXTURE_ID_START + 1).is_err());
+    }
+}
@@ -0,0 +1,438 @@
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::Path,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::EguiBackend;
+
+/// Name of the replay file format, written in the header.
+const REPLAY_FORMAT: &str = "egui_glfw_replay";
+
+/// Version of the replay file format written by [`InputRecorder`].
+/// It is bumped on incompatible changes, [`InputPlayer`] only plays
+/// replays of this version.
+pub const REPLAY_FORMAT_VERSION: u32 = 1;
+
+/// Header of a replay file, its first line.
+#[derive(Debug, Serialize, Deserialize)]
+struct ReplayHeader {
+    format: String,
+    version: u32,
+}
+
+/// Input event of a replay.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ReplayEvent {
+    /// Event given to [`EguiBackend::handle_event()`].
+    Window(glfw::WindowEvent),
+    /// Event given to [`EguiBackend::push_event()`].
+    Egui(egui::Event),
+}
+
+/// Record of a replay file, a line after the header.
+#[derive(Debug, Serialize, Deserialize)]
+enum ReplayRecord {
+    Event(ReplayEvent),
+    /// [`EguiBackend::begin_pass()`], ends the frame of the events
+    /// recorded since the previous one.
+    Frame {
+        screen_rect: Option<egui::Rect>,
+        time: f64,
+    },
+}
+
+/// Frame of a replay.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplayFrame {
+    /// Events given to the backend before the pass, in order.
+    pub events: Vec<ReplayEvent>,
+    /// [`egui::RawInput::screen_rect`] of the pass.
+    pub screen_rect: Option<egui::Rect>,
+    /// [`egui::RawInput::time`] of the pass.
+    pub time: f64,
+}
+
+/// Error of reading a replay with [`InputPlayer`].
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(io::Error),
+    /// Not a replay file.
+    Format,
+    /// Replay of another version of the format.
+    Version(u32),
+    /// Invalid record at the line (starting at 1).
+    Record {
+        line: usize,
+        error: serde_json::Error,
+    },
+}
+
+impl std::fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReplayError::Io(error) => write!(f, "io error: {}", error),
+            ReplayError::Format => write!(f, "not a replay file"),
+            ReplayError::Version(version) => write!(
+                f,
+                "replay format version {} is not supported, expected {}",
+                version, REPLAY_FORMAT_VERSION
+            ),
+            ReplayError::Record { line, error } => {
+                write!(f, "invalid record at line {}: {}", line, error)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ReplayError::Io(error) => Some(error),
+            ReplayError::Record { error, .. } => Some(error),
+            ReplayError::Format | ReplayError::Version(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for ReplayError {
+    fn from(error: io::Error) -> Self {
+        ReplayError::Io(error)
+    }
+}
+
+/// Records the input given to the [`EguiBackend`] to replay it later
+/// with [`InputPlayer`], see [`EguiBackend::set_input_recorder()`].
+///
+/// The replay is written as JSON lines: a header with the format
+/// version followed by a line per event and per frame. The writer is
+/// flushed at every frame so that the replay is kept if the
+/// application crashes.
+pub struct InputRecorder {
+    writer: Box<dyn Write>,
+    /// Has writing failed, nothing is recorded after that.
+    failed: bool,
+}
+
+impl InputRecorder {
+    /// Create a new [`InputRecorder`] that writes the replay to
+    /// `writer`.
+    pub fn new(writer: impl Write + 'static) -> io::Result<Self> {
+        let mut writer: Box<dyn Write> = Box::new(writer);
+        let header = ReplayHeader {
+            format: REPLAY_FORMAT.to_string(),
+            version: REPLAY_FORMAT_VERSION,
+        };
+        serde_json::to_writer(&mut writer, &header)?;
+        writer.write_all(b"\n")?;
+        Ok(Self {
+            writer,
+            failed: false,
+        })
+    }
+
+    /// Create a new [`InputRecorder`] that writes the replay to the
+    /// file at `path`, the file is truncated if it exists.
+    pub fn create(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::new(BufWriter::new(File::create(path)?))
+    }
+
+    /// Record an event given to [`EguiBackend::handle_event()`].
+    pub fn record_window_event(&mut self, event: &glfw::WindowEvent) {
+        self.record(&ReplayRecord::Event(ReplayEvent::Window(event.clone())));
+    }
+
+    /// Record an event given to [`EguiBackend::push_event()`].
+    pub fn record_egui_event(&mut self, event: &egui::Event) {
+        self.record(&ReplayRecord::Event(ReplayEvent::Egui(event.clone())));
+    }
+
+    /// Record the end of a frame, that is a pass begun with
+    /// `screen_rect` at `time`.
+    pub fn record_frame(&mut self, screen_rect: Option<egui::Rect>, time: f64) {
+        self.record(&ReplayRecord::Frame { screen_rect, time });
+        if !self.failed {
+            if let Err(error) = self.writer.flush() {
+                self.fail(error);
+            }
+        }
+    }
+
+    /// Flush the replay written so far.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// Write `record` as a line.
+    fn record(&mut self, record: &ReplayRecord) {
+        if self.failed {
+            return;
+        }
+
+        let result = serde_json::to_writer(&mut self.writer, record)
+            .map_err(io::Error::from)
+            .and_then(|()| self.writer.write_all(b"\n"));
+        if let Err(error) = result {
+            self.fail(error);
+        }
+    }
+
+    /// Stop recording after a write failed, a partial line would make
+    /// the rest of the replay unreadable.
+    fn fail(&mut self, _error: io::Error) {
+        #[cfg(feature = "tracing")]
+        tracing::warn!("stopped recording the input: {}", _error);
+        self.failed = true;
+    }
+}
+
+/// Plays a replay recorded by [`InputRecorder`] back into an
+/// [`EguiBackend`] frame by frame.
+///
+/// # Example
+///
+/// ```no_run
+/// let mut player = egui_glfw::InputPlayer::open("bug.replay")?;
+/// while player.play_frame(&mut egui, &window) {
+///     egui.begin_pass(&window, &mut glfw);
+///     ui(egui.get_egui_ctx());
+///     egui.end_pass((width as _, height as _));
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct InputPlayer {
+    frames: Vec<ReplayFrame>,
+    /// Index of the next frame to play.
+    next_frame: usize,
+}
+
+impl InputPlayer {
+    /// Create a new [`InputPlayer`] of the replay read from `reader`.
+    ///
+    /// Events recorded after the last frame are dropped since they
+    /// never reached egui.
+    pub fn new(reader: impl BufRead) -> Result<Self, ReplayError> {
+        let mut lines = reader.lines();
+
+        let header = lines.next().ok_or(ReplayError::Format)??;
+        let header: ReplayHeader =
+            serde_json::from_str(&header).map_err(|_| ReplayError::Format)?;
+        if header.format != REPLAY_FORMAT {
+            return Err(ReplayError::Format);
+        }
+        if header.version != REPLAY_FORMAT_VERSION {
+            return Err(ReplayError::Version(header.version));
+        }
+
+        let mut frames = Vec::new();
+        let mut events = Vec::new();
+        for (index, line) in lines.enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let record = serde_json::from_str(&line).map_err(|error| ReplayError::Record {
+                // the header is the first line
+                line: index + 2,
+                error,
+            })?;
+            match record {
+                ReplayRecord::Event(event) => events.push(event),
+                ReplayRecord::Frame { screen_rect, time } => frames.push(ReplayFrame {
+                    events: std::mem::take(&mut events),
+                    screen_rect,
+                    time,
+                }),
+            }
+        }
+
+        Ok(Self {
+            frames,
+            next_frame: 0,
+        })
+    }
+
+    /// Create a new [`InputPlayer`] of the replay in the file at
+    /// `path`.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, ReplayError> {
+        Self::new(BufReader::new(File::open(path)?))
+    }
+
+    /// Get the frames of the replay.
+    pub fn get_frames(&self) -> &[ReplayFrame] {
+        &self.frames
+    }
+
+    /// Get the index of the next frame to play.
+    pub fn get_next_frame(&self) -> usize {
+        self.next_frame
+    }
+
+    /// Have all the frames been played.
+    pub fn is_finished(&self) -> bool {
+        self.next_frame >= self.frames.len()
+    }
+
+    /// Play the next frame: give its events to `egui` and make the
+    /// next [`EguiBackend::begin_pass()`] use its screen rect and
+    /// time.
+    ///
+    /// Returns `false` once all the frames have been played.
+    ///
+    /// The events are handled with `window`, it should have the size
+    /// and content scale of the recorded window for the replay to be
+    /// faithful. The paste events depend on the clipboard of `egui`,
+    /// consider a [`crate::MemoryClipboard`] to not depend on the
+    /// clipboard of the system.
+    pub fn play_frame(&mut self, egui: &mut EguiBackend, window: &glfw::Window) -> bool {
+        let Some(frame) = self.frames.get(self.next_frame) else {
+            return false;
+        };
+        self.next_frame += 1;
+
+        frame.events.iter().for_each(|event| match event {
+            ReplayEvent::Window(event) => {
+                egui.handle_event(event, window);
+            }
+            ReplayEvent::Egui(event) => egui.push_event(event.clone()),
+        });
+        egui.set_replay_pass(frame.screen_rect, frame.time);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::{cell::RefCell, rc::Rc};
+
+    /// Writer into a buffer shared with its clones.
+    #[derive(Debug, Default, Clone)]
+    struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn replay_round_trip() {
+        let buffer = SharedBuffer::default();
+        let mut recorder = InputRecorder::new(buffer.clone()).unwrap();
+
+        let key = glfw::WindowEvent::Key(
+            glfw::Key::A,
+            38,
+            glfw::Action::Press,
+            glfw::Modifiers::Shift | glfw::Modifiers::NumLock,
+        );
+        let cursor = glfw::WindowEvent::CursorPos(10.5, 20.25);
+        let screen_rect = egui::Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(640.0, 480.0));
+
+        recorder.record_frame(Some(screen_rect), 0.0);
+        recorder.record_window_event(&key);
+        recorder.record_egui_event(&egui::Event::Copy);
+        recorder.record_window_event(&cursor);
+        recorder.record_frame(None, 0.016);
+        // never reached egui
+        recorder.record_window_event(&glfw::WindowEvent::Char('a'));
+
+        let replay = buffer.0.borrow().clone();
+        let player = InputPlayer::new(replay.as_slice()).unwrap();
+        assert_eq!(
+            player.get_frames(),
+            &[
+                ReplayFrame {
+                    events: Vec::new(),
+                    screen_rect: Some(screen_rect),
+                    time: 0.0,
+                },
+                ReplayFrame {
+                    events: vec![
+                        ReplayEvent::Window(key),
+                        ReplayEvent::Egui(egui::Event::Copy),
+                        ReplayEvent::Window(cursor),
+                    ],
+                    screen_rect: None,
+                    time: 0.016,
+                },
+            ]
+        );
+        assert!(!player.is_finished());
+    }
+
+    #[test]
+    fn replayed_click_is_at_the_recorded_cursor() {
+        let buffer = SharedBuffer::default();
+        let mut recorder = InputRecorder::new(buffer.clone()).unwrap();
+        recorder.record_window_event(&glfw::WindowEvent::CursorPos(30.0, 50.0));
+        recorder.record_window_event(&glfw::WindowEvent::MouseButton(
+            glfw::MouseButton::Button1,
+            glfw::Action::Press,
+            glfw::Modifiers::empty(),
+        ));
+        recorder.record_frame(None, 0.0);
+
+        let replay = buffer.0.borrow().clone();
+        let player = InputPlayer::new(replay.as_slice()).unwrap();
+        let mut input = crate::input::Input::new(2.0);
+        player.get_frames()[0]
+            .events
+            .iter()
+            .for_each(|event| match event {
+                ReplayEvent::Window(event) => input.push_window_event(event, 2.0),
+                ReplayEvent::Egui(event) => input.push_event(event.clone()),
+            });
+
+        assert_eq!(
+            input.take().events,
+            vec![
+                egui::Event::PointerMoved(egui::pos2(15.0, 25.0)),
+                egui::Event::PointerButton {
+                    pos: egui::pos2(15.0, 25.0),
+                    button: egui::PointerButton::Primary,
+                    pressed: true,
+                    modifiers: egui::Modifiers::NONE,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn replay_header_is_checked() {
+        assert!(matches!(
+            InputPlayer::new("".as_bytes()),
+            Err(ReplayError::Format)
+        ));
+        assert!(matches!(
+            InputPlayer::new("{\"Frame\":{\"screen_rect\":null,\"time\":0.0}}\n".as_bytes()),
+            Err(ReplayError::Format)
+        ));
+        assert!(matches!(
+            InputPlayer::new("{\"format\":\"egui_glfw_replay\",\"version\":0}\n".as_bytes()),
+            Err(ReplayError::Version(0))
+        ));
+    }
+
+    #[test]
+    fn replay_invalid_record_line() {
+        let replay = format!(
+            "{{\"format\":\"egui_glfw_replay\",\"version\":{}}}\n\
+             {{\"Frame\":{{\"screen_rect\":null,\"time\":0.0}}}}\n\
+             {{\"Frame\":0}}\n",
+            REPLAY_FORMAT_VERSION
+        );
+        assert!(matches!(
+            InputPlayer::new(replay.as_bytes()),
+            Err(ReplayError::Record { line: 3, .. })
+        ));
+    }
+}
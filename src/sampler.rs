@@ -0,0 +1,109 @@
+use std::convert::TryInto;
+
+use egui::epaint::ahash::AHashMap;
+
+use crate::texture::{EguiTextureFilterExtend, EguiTextureWrapModeExtend};
+
+/// Cache of OpenGL sampler objects keyed by
+/// [`egui::TextureOptions`].
+///
+/// Sampling state is kept separate from the texture storage so that
+/// the same texture can be sampled with different
+/// [`egui::TextureOptions`] without recreating the texture.
+pub struct SamplerCache {
+    samplers: AHashMap<egui::TextureOptions, gl::types::GLuint>,
+}
+
+impl SamplerCache {
+    /// Create a new empty [`SamplerCache`].
+    pub fn new() -> Self {
+        Self {
+            samplers: AHashMap::new(),
+        }
+    }
+
+    /// Get the OpenGL sampler object for the given
+    /// [`egui::TextureOptions`], the sampler is created if it does
+    /// not exist already.
+    pub fn get(&mut self, texture_options: &egui::TextureOptions) -> gl::types::GLuint {
+        *self
+            .samplers
+            .entry(*texture_options)
+            .or_insert_with(|| Self::gen_gl_sampler(texture_options))
+    }
+
+    /// Bind the sampler for the given [`egui::TextureOptions`] to the
+    /// given texture unit.
+    pub fn bind(
+        &mut self,
+        texture_unit: gl::types::GLuint,
+        texture_options: &egui::TextureOptions,
+    ) {
+        let sampler = self.get(texture_options);
+        unsafe {
+            gl::BindSampler(texture_unit, sampler);
+        }
+    }
+
+    /// Unbind any sampler from the given texture unit so that the
+    /// sampling state of the texture itself is used.
+    pub fn unbind(texture_unit: gl::types::GLuint) {
+        unsafe {
+            gl::BindSampler(texture_unit, 0);
+        }
+    }
+
+    fn gen_gl_sampler(texture_options: &egui::TextureOptions) -> gl::types::GLuint {
+        let mut gl_sampler = 0;
+        unsafe {
+            gl::GenSamplers(1, &mut gl_sampler);
+        }
+        assert_ne!(gl_sampler, 0);
+
+        unsafe {
+            // wrapping method
+            gl::SamplerParameteri(
+                gl_sampler,
+                gl::TEXTURE_WRAP_S,
+                texture_options.wrap_mode.to_gl().try_into().unwrap(),
+            );
+            gl::SamplerParameteri(
+                gl_sampler,
+                gl::TEXTURE_WRAP_T,
+                texture_options.wrap_mode.to_gl().try_into().unwrap(),
+            );
+
+            // filter method
+            gl::SamplerParameteri(
+                gl_sampler,
+                gl::TEXTURE_MIN_FILTER,
+                texture_options
+                    .minification
+                    .to_gl_min_filter(texture_options.mipmap_mode)
+                    .try_into()
+                    .unwrap(),
+            );
+            gl::SamplerParameteri(
+                gl_sampler,
+                gl::TEXTURE_MAG_FILTER,
+                texture_options.magnification.to_gl().try_into().unwrap(),
+            );
+        }
+
+        gl_sampler
+    }
+}
+
+impl Default for SamplerCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for SamplerCache {
+    fn drop(&mut self) {
+        self.samplers.values().for_each(|gl_sampler| unsafe {
+            gl::DeleteSamplers(1, gl_sampler);
+        });
+    }
+}
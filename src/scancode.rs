@@ -0,0 +1,270 @@
+use std::convert::TryFrom;
+
+/// evdev key code of the escape key, used to detect the offset of
+/// the GLFW scancodes from the evdev key codes.
+const EVDEV_KEY_ESC: glfw::Scancode = 1;
+
+/// Get the physical [`egui::Key`] for the GLFW `scancode` on Linux.
+///
+/// GLFW reports X11 keycodes on X11, which are the evdev key codes
+/// offset by 8, and the evdev key codes on Wayland. The offset is
+/// detected from `escape_scancode`, the scancode of the escape key
+/// (see [`glfw::get_key_scancode()`]).
+pub fn physical_key_from_scancode(
+    scancode: glfw::Scancode,
+    escape_scancode: glfw::Scancode,
+) -> Option<egui::Key> {
+    let offset = escape_scancode - EVDEV_KEY_ESC;
+    let code = u32::try_from(scancode.checked_sub(offset)?).ok()?;
+    physical_key_from_evdev(code)
+}
+
+/// Get the physical [`egui::Key`] for the evdev key code `code`
+/// (see `linux/input-event-codes.h`). The key is named after its
+/// position on the US layout.
+pub fn physical_key_from_evdev(code: u32) -> Option<egui::Key> {
+    Some(match code {
+        1 => egui::Key::Escape,
+        2 => egui::Key::Num1,
+        3 => egui::Key::Num2,
+        4 => egui::Key::Num3,
+        5 => egui::Key::Num4,
+        6 => egui::Key::Num5,
+        7 => egui::Key::Num6,
+        8 => egui::Key::Num7,
+        9 => egui::Key::Num8,
+        10 => egui::Key::Num9,
+        11 => egui::Key::Num0,
+        12 => egui::Key::Minus,
+        13 => egui::Key::Equals,
+        14 => egui::Key::Backspace,
+        15 => egui::Key::Tab,
+
+        16 => egui::Key::Q,
+        17 => egui::Key::W,
+        18 => egui::Key::E,
+        19 => egui::Key::R,
+        20 => egui::Key::T,
+        21 => egui::Key::Y,
+        22 => egui::Key::U,
+        23 => egui::Key::I,
+        24 => egui::Key::O,
+        25 => egui::Key::P,
+        26 => egui::Key::OpenBracket,
+        27 => egui::Key::CloseBracket,
+        28 => egui::Key::Enter,
+
+        30 => egui::Key::A,
+        31 => egui::Key::S,
+        32 => egui::Key::D,
+        33 => egui::Key::F,
+        34 => egui::Key::G,
+        35 => egui::Key::H,
+        36 => egui::Key::J,
+        37 => egui::Key::K,
+        38 => egui::Key::L,
+        39 => egui::Key::Semicolon,
+        40 => egui::Key::Quote,
+        41 => egui::Key::Backtick,
+        43 => egui::Key::Backslash,
+
+        44 => egui::Key::Z,
+        45 => egui::Key::X,
+        46 => egui::Key::C,
+        47 => egui::Key::V,
+        48 => egui::Key::B,
+        49 => egui::Key::N,
+        50 => egui::Key::M,
+        51 => egui::Key::Comma,
+        52 => egui::Key::Period,
+        53 => egui::Key::Slash,
+        57 => egui::Key::Space,
+
+        59 => egui::Key::F1,
+        60 => egui::Key::F2,
+        61 => egui::Key::F3,
+        62 => egui::Key::F4,
+        63 => egui::Key::F5,
+        64 => egui::Key::F6,
+        65 => egui::Key::F7,
+        66 => egui::Key::F8,
+        67 => egui::Key::F9,
+        68 => egui::Key::F10,
+        87 => egui::Key::F11,
+        88 => egui::Key::F12,
+
+        // keypad, egui does not distinguish the keypad keys
+        71 => egui::Key::Num7,
+        72 => egui::Key::Num8,
+        73 => egui::Key::Num9,
+        74 => egui::Key::Minus,
+        75 => egui::Key::Num4,
+        76 => egui::Key::Num5,
+        77 => egui::Key::Num6,
+        78 => egui::Key::Plus,
+        79 => egui::Key::Num1,
+        80 => egui::Key::Num2,
+        81 => egui::Key::Num3,
+        82 => egui::Key::Num0,
+        83 => egui::Key::Period,
+        96 => egui::Key::Enter,
+        98 => egui::Key::Slash,
+        117 => egui::Key::Equals,
+        121 => egui::Key::Comma,
+
+        102 => egui::Key::Home,
+        103 => egui::Key::ArrowUp,
+        104 => egui::Key::PageUp,
+        105 => egui::Key::ArrowLeft,
+        106 => egui::Key::ArrowRight,
+        107 => egui::Key::End,
+        108 => egui::Key::ArrowDown,
+        109 => egui::Key::PageDown,
+        110 => egui::Key::Insert,
+        111 => egui::Key::Delete,
+
+        133 => egui::Key::Copy,
+        135 => egui::Key::Paste,
+        137 => egui::Key::Cut,
+
+        183 => egui::Key::F13,
+        184 => egui::Key::F14,
+        185 => egui::Key::F15,
+        186 => egui::Key::F16,
+        187 => egui::Key::F17,
+        188 => egui::Key::F18,
+        189 => egui::Key::F19,
+        190 => egui::Key::F20,
+        191 => egui::Key::F21,
+        192 => egui::Key::F22,
+        193 => egui::Key::F23,
+        194 => egui::Key::F24,
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Scancode of the escape key on X11.
+    const X11_ESCAPE: glfw::Scancode = 9;
+    /// Scancode of the escape key on Wayland.
+    const WAYLAND_ESCAPE: glfw::Scancode = 1;
+
+    #[test]
+    fn evdev_letters_follow_us_layout() {
+        let rows: [(u32, &[egui::Key]); 3] = [
+            (
+                16,
+                &[
+                    egui::Key::Q,
+                    egui::Key::W,
+                    egui::Key::E,
+                    egui::Key::R,
+                    egui::Key::T,
+                    egui::Key::Y,
+                    egui::Key::U,
+                    egui::Key::I,
+                    egui::Key::O,
+                    egui::Key::P,
+                ],
+            ),
+            (
+                30,
+                &[
+                    egui::Key::A,
+                    egui::Key::S,
+                    egui::Key::D,
+                    egui::Key::F,
+                    egui::Key::G,
+                    egui::Key::H,
+                    egui::Key::J,
+                    egui::Key::K,
+                    egui::Key::L,
+                ],
+            ),
+            (
+                44,
+                &[
+                    egui::Key::Z,
+                    egui::Key::X,
+                    egui::Key::C,
+                    egui::Key::V,
+                    egui::Key::B,
+                    egui::Key::N,
+                    egui::Key::M,
+                ],
+            ),
+        ];
+        rows.iter().for_each(|(first_code, keys)| {
+            keys.iter().zip(*first_code..).for_each(|(key, code)| {
+                assert_eq!(physical_key_from_evdev(code), Some(*key), "code {}", code);
+            });
+        });
+    }
+
+    #[test]
+    fn evdev_unmapped_codes() {
+        // modifiers and lock keys have no egui key
+        [0, 29, 42, 54, 56, 58, 69, 70, 97, 100, 125, 126, 999]
+            .iter()
+            .for_each(|code| assert_eq!(physical_key_from_evdev(*code), None, "code {}", code));
+    }
+
+    #[test]
+    fn scancode_x11_offset() {
+        assert_eq!(
+            physical_key_from_scancode(9, X11_ESCAPE),
+            Some(egui::Key::Escape)
+        );
+        // WASD
+        assert_eq!(
+            physical_key_from_scancode(25, X11_ESCAPE),
+            Some(egui::Key::W)
+        );
+        assert_eq!(
+            physical_key_from_scancode(38, X11_ESCAPE),
+            Some(egui::Key::A)
+        );
+        assert_eq!(
+            physical_key_from_scancode(39, X11_ESCAPE),
+            Some(egui::Key::S)
+        );
+        assert_eq!(
+            physical_key_from_scancode(40, X11_ESCAPE),
+            Some(egui::Key::D)
+        );
+        assert_eq!(
+            physical_key_from_scancode(65, X11_ESCAPE),
+            Some(egui::Key::Space)
+        );
+        assert_eq!(
+            physical_key_from_scancode(111, X11_ESCAPE),
+            Some(egui::Key::ArrowUp)
+        );
+    }
+
+    #[test]
+    fn scancode_wayland_offset() {
+        assert_eq!(
+            physical_key_from_scancode(1, WAYLAND_ESCAPE),
+            Some(egui::Key::Escape)
+        );
+        assert_eq!(
+            physical_key_from_scancode(17, WAYLAND_ESCAPE),
+            Some(egui::Key::W)
+        );
+        assert_eq!(
+            physical_key_from_scancode(30, WAYLAND_ESCAPE),
+            Some(egui::Key::A)
+        );
+    }
+
+    #[test]
+    fn scancode_below_offset() {
+        assert_eq!(physical_key_from_scancode(0, X11_ESCAPE), None);
+        assert_eq!(physical_key_from_scancode(-1, X11_ESCAPE), None);
+        assert_eq!(physical_key_from_scancode(-1, WAYLAND_ESCAPE), None);
+    }
+}
@@ -116,7 +116,7 @@ impl Shader {
         unsafe {
             let mut success: gl::types::GLint = -10;
             gl::GetShaderiv(vertex_shader, gl::COMPILE_STATUS, &mut success);
-            if success != gl::TRUE.into() {
+            if success != gl::types::GLint::from(gl::TRUE) {
                 eprintln!("vertex didn't compile");
 
                 let log = get_shader_error_log(vertex_shader);
@@ -139,7 +139,7 @@ impl Shader {
         unsafe {
             let mut success: gl::types::GLint = -10;
             gl::GetShaderiv(fragment_shader, gl::COMPILE_STATUS, &mut success);
-            if success != gl::TRUE.into() {
+            if success != gl::types::GLint::from(gl::TRUE) {
                 eprintln!("fragment didn't compile");
 
                 let log = get_shader_error_log(fragment_shader);
@@ -157,7 +157,7 @@ impl Shader {
         unsafe {
             let mut success: gl::types::GLint = -10;
             gl::GetProgramiv(shader_program, gl::LINK_STATUS, &mut success);
-            if success != gl::TRUE.into() {
+            if success != gl::types::GLint::from(gl::TRUE) {
                 eprintln!("program not linked");
 
                 let log = get_program_error_log(shader_program);
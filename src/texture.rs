@@ -1,14 +1,151 @@
-use std::{borrow::Cow, convert::TryInto};
+use std::{borrow::Cow, convert::TryInto, marker::PhantomData};
+
+use crate::{texture_upload::TextureUpload, util};
+
+/// Policy for retaining the CPU side copy of the pixels of a
+/// texture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum PixelRetention {
+    /// Upload the texture to the GPU immediately and release the CPU
+    /// side copy of the pixels.
+    ///
+    /// Requires an active OpenGL context when the policy is set.
+    GpuOnly,
+    /// Always keep the CPU side copy of the pixels, so the texture
+    /// can be uploaded again after [`Texture::cleanup_opengl()`].
+    ///
+    /// This is the default, only the textures of egui (see
+    /// [`Texture::from_egui()`]) release their pixels by default.
+    #[default]
+    KeepCpuCopy,
+    /// Keep the CPU side copy of the pixels until the texture is
+    /// uploaded to the GPU (which happens lazily), after which the
+    /// copy is released.
+    KeepUntilUploaded,
+}
+
+/// Pixel format of a [`Texture`].
+pub trait PixelFormat {
+    /// A single pixel of the format.
+    type Pixel: Copy + 'static;
+
+    /// OpenGL internal format of the texture.
+    const INTERNAL_FORMAT: gl::types::GLenum;
+    /// OpenGL format of the pixel data.
+    const FORMAT: gl::types::GLenum;
+    /// OpenGL type of the components of the pixel data.
+    const TYPE: gl::types::GLenum;
+    /// Swizzle applied to the (red, green, blue, alpha) channels when
+    /// the texture is sampled, for example so that single channel
+    /// data is displayed as greyscale.
+    const SWIZZLE: [gl::types::GLenum; 4];
+    /// Number of bytes a pixel takes on the GPU in the
+    /// [`Self::INTERNAL_FORMAT`], which can differ from the size of
+    /// [`Self::Pixel`] (for example, half floats given as [`f32`]).
+    const GPU_BYTES_PER_PIXEL: usize;
+}
+
+/// 4 channels, [`u8`] each.
+pub struct RGBA8;
+
+impl PixelFormat for RGBA8 {
+    type Pixel = (u8, u8, u8, u8);
+
+    const INTERNAL_FORMAT: gl::types::GLenum = gl::RGBA8;
+    const FORMAT: gl::types::GLenum = gl::RGBA;
+    const TYPE: gl::types::GLenum = gl::UNSIGNED_BYTE;
+    const SWIZZLE: [gl::types::GLenum; 4] = [gl::RED, gl::GREEN, gl::BLUE, gl::ALPHA];
+    const GPU_BYTES_PER_PIXEL: usize = 4;
+}
+
+/// Single channel, [`u8`]. Displayed as greyscale.
+pub struct R8;
+
+impl PixelFormat for R8 {
+    type Pixel = u8;
+
+    const INTERNAL_FORMAT: gl::types::GLenum = gl::R8;
+    const FORMAT: gl::types::GLenum = gl::RED;
+    const TYPE: gl::types::GLenum = gl::UNSIGNED_BYTE;
+    const SWIZZLE: [gl::types::GLenum; 4] = [gl::RED, gl::RED, gl::RED, gl::ONE];
+    const GPU_BYTES_PER_PIXEL: usize = 1;
+}
+
+/// 2 channels, [`u8`] each. Displayed with the channels as red and
+/// green.
+pub struct RG8;
+
+impl PixelFormat for RG8 {
+    type Pixel = (u8, u8);
+
+    const INTERNAL_FORMAT: gl::types::GLenum = gl::RG8;
+    const FORMAT: gl::types::GLenum = gl::RG;
+    const TYPE: gl::types::GLenum = gl::UNSIGNED_BYTE;
+    const SWIZZLE: [gl::types::GLenum; 4] = [gl::RED, gl::GREEN, gl::ZERO, gl::ONE];
+    const GPU_BYTES_PER_PIXEL: usize = 2;
+}
+
+/// 4 channels, stored as half floats on the GPU, provided as [`f32`].
+pub struct RGBA16F;
+
+impl PixelFormat for RGBA16F {
+    type Pixel = (f32, f32, f32, f32);
+
+    const INTERNAL_FORMAT: gl::types::GLenum = gl::RGBA16F;
+    const FORMAT: gl::types::GLenum = gl::RGBA;
+    const TYPE: gl::types::GLenum = gl::FLOAT;
+    const SWIZZLE: [gl::types::GLenum; 4] = [gl::RED, gl::GREEN, gl::BLUE, gl::ALPHA];
+    const GPU_BYTES_PER_PIXEL: usize = 8;
+}
+
+/// 4 channels, [`f32`] each.
+pub struct RGBA32F;
+
+impl PixelFormat for RGBA32F {
+    type Pixel = (f32, f32, f32, f32);
+
+    const INTERNAL_FORMAT: gl::types::GLenum = gl::RGBA32F;
+    const FORMAT: gl::types::GLenum = gl::RGBA;
+    const TYPE: gl::types::GLenum = gl::FLOAT;
+    const SWIZZLE: [gl::types::GLenum; 4] = [gl::RED, gl::GREEN, gl::BLUE, gl::ALPHA];
+    const GPU_BYTES_PER_PIXEL: usize = 16;
+}
+
+/// Depth, [`f32`]. Displayed as greyscale, useful for displaying
+/// shadow maps.
+pub struct Depth32F;
+
+impl PixelFormat for Depth32F {
+    type Pixel = f32;
+
+    const INTERNAL_FORMAT: gl::types::GLenum = gl::DEPTH_COMPONENT32F;
+    const FORMAT: gl::types::GLenum = gl::DEPTH_COMPONENT;
+    const TYPE: gl::types::GLenum = gl::FLOAT;
+    const SWIZZLE: [gl::types::GLenum; 4] = [gl::RED, gl::RED, gl::RED, gl::ONE];
+    const GPU_BYTES_PER_PIXEL: usize = 4;
+}
 
 /// GPU Texture RGBA8. Each pixel has 4 channels, [`u8`] each.
-pub struct TextureRGBA8 {
+pub type TextureRGBA8 = Texture<RGBA8>;
+
+/// GPU Texture with pixels of the given [`PixelFormat`].
+///
+/// The texture can be shown by egui by using
+/// [`egui::TextureId::User`] with [`Self::get_gl_tex()`].
+pub struct Texture<F: PixelFormat> {
     /// Width of the texture.
     width: usize,
     /// Height of the texture.
     height: usize,
 
     /// Pixels of the image stored from bottom left row wise.
-    pixels: Vec<(u8, u8, u8, u8)>,
+    ///
+    /// [`None`] if the pixels were released based on the
+    /// [`PixelRetention`] policy.
+    pixels: Option<Vec<F::Pixel>>,
+
+    /// [`PixelRetention`] policy of the texture.
+    pixel_retention: PixelRetention,
 
     /// [`egui::TextureOptions`] for the current texture.
     texture_options: egui::TextureOptions,
@@ -17,29 +154,43 @@ pub struct TextureRGBA8 {
     ///
     /// If [`None`] if the texture is not yet uploaded to the GPU.
     gl_tex: Option<gl::types::GLuint>,
+    /// GLFW window whose OpenGL context was current when the texture
+    /// was uploaded to the GPU, see
+    /// [`util::get_current_gl_context()`].
+    gl_context: usize,
+
+    format: PhantomData<F>,
 }
 
-impl TextureRGBA8 {
-    /// Create a [`TextureRGBA8`] from pixels. The pixels provided
-    /// must follow the pixel memory layout mapping of bottom left
-    /// left to right rows.
+impl<F: PixelFormat> Texture<F> {
+    /// Create a [`Texture`] from pixels. The pixels provided must
+    /// follow the pixel memory layout mapping of bottom left left to
+    /// right rows.
     pub fn from_pixels(
         width: usize,
         height: usize,
-        pixels: Vec<(u8, u8, u8, u8)>,
+        pixels: Vec<F::Pixel>,
         texture_options: egui::TextureOptions,
     ) -> Self {
         assert_eq!(pixels.len(), width * height);
         Self {
             width,
             height,
-            pixels,
+            pixels: Some(pixels),
+            pixel_retention: PixelRetention::default(),
             gl_tex: None,
+            gl_context: 0,
             texture_options,
+            format: PhantomData,
         }
     }
+}
 
+impl Texture<RGBA8> {
     /// Create a [`TextureRGBA8`] from an egui ImageDelta.
+    ///
+    /// The texture uses [`PixelRetention::KeepUntilUploaded`] since
+    /// egui sends the whole image again when it is needed.
     pub fn from_egui(delta: &egui::epaint::image::ImageDelta) -> Option<Self> {
         // the delta should be for the whole image, the total image
         // size cannot be determined to update only a portion of the
@@ -57,7 +208,7 @@ impl TextureRGBA8 {
         Some(Self {
             width: image.width(),
             height: image.height(),
-            pixels: match image {
+            pixels: Some(match image {
                 egui::ImageData::Color(image) => image
                     .pixels
                     .chunks(image.width())
@@ -75,32 +226,37 @@ impl TextureRGBA8 {
                     .rev()
                     .flat_map(|row| row.iter().copied())
                     .collect(),
-            },
+            }),
+            pixel_retention: PixelRetention::KeepUntilUploaded,
             texture_options: delta.options,
             gl_tex: None,
+            gl_context: 0,
+            format: PhantomData,
         })
     }
 
     /// Update the texture from egui's ImageDelta.
+    ///
+    /// Partial deltas update only the changed region of the texture
+    /// on the GPU (if it was uploaded already).
     pub fn update_from_egui(&mut self, delta: &egui::epaint::image::ImageDelta) {
         // if the whole image has changed, then just replace self
         if delta.is_whole() {
+            let pixel_retention = self.pixel_retention;
             *self = Self::from_egui(delta).unwrap();
+            self.set_pixel_retention(pixel_retention);
             return;
         }
 
-        // TODO: need to optimize this, shouldn't delete the entire
-        // texture from the GPU and resend everything. It does not
-        // take advantage of the delta that is provided. Make sure
-        // `self.texture_options` match the delta's
-        // [`egui::TextureOptions`]. For the texture options, it might
-        // make sense to use a texture sampler instead of assigning
-        // the sampler options to the texture itself.
-
-        // delete the entire texture from the GPU if it was previously
-        // uploaded
-        if self.gl_tex.is_some() {
-            self.cleanup_opengl();
+        if self.texture_options != delta.options {
+            self.texture_options = delta.options;
+            if let Some(gl_tex) = self.gl_tex {
+                unsafe {
+                    gl::BindTexture(gl::TEXTURE_2D, gl_tex);
+                }
+                Self::set_gl_texture_options(&self.texture_options);
+                self.generate_mipmaps_if_needed();
+            }
         }
 
         let (delta_image_pixels, delta_image_width) = match &delta.image {
@@ -114,30 +270,196 @@ impl TextureRGBA8 {
         // Self requires (0, 0) as bottom left, so need to update by
         // "reversing" (flip vertically) the image during the update
         let start_pos = delta.pos.unwrap();
-        self.pixels
-            .chunks_mut(self.width)
-            .rev()
-            .enumerate()
-            .skip(start_pos[1])
-            .enumerate()
-            .map_while(|(y, (row_index, row))| {
-                (row_index < (start_pos[1] + delta.image.height())).then_some((row, y))
-            })
-            .for_each(|(row, y)| {
-                row.iter_mut()
-                    .enumerate()
-                    .skip(start_pos[0])
-                    .enumerate()
-                    .map_while(|(x, (column_index, pixel))| {
-                        (column_index < (start_pos[0] + delta.image.width())).then_some((pixel, x))
-                    })
-                    .for_each(|(pixel, x)| {
-                        let new_pixel = delta_image_pixels[y * delta_image_width + x];
-                        *pixel = (new_pixel.r(), new_pixel.g(), new_pixel.b(), new_pixel.a())
-                    });
-            });
+        assert!(start_pos[0] + delta.image.width() <= self.width);
+        assert!(start_pos[1] + delta.image.height() <= self.height);
+        // the CPU side copy may have been released, in which case
+        // only the GPU side texture is updated
+        if let Some(pixels) = self.pixels.as_mut() {
+            pixels
+                .chunks_mut(self.width)
+                .rev()
+                .enumerate()
+                .skip(start_pos[1])
+                .enumerate()
+                .map_while(|(y, (row_index, row))| {
+                    (row_index < (start_pos[1] + delta.image.height())).then_some((row, y))
+                })
+                .for_each(|(row, y)| {
+                    row.iter_mut()
+                        .enumerate()
+                        .skip(start_pos[0])
+                        .enumerate()
+                        .map_while(|(x, (column_index, pixel))| {
+                            (column_index < (start_pos[0] + delta.image.width()))
+                                .then_some((pixel, x))
+                        })
+                        .for_each(|(pixel, x)| {
+                            let new_pixel = delta_image_pixels[y * delta_image_width + x];
+                            *pixel = (new_pixel.r(), new_pixel.g(), new_pixel.b(), new_pixel.a())
+                        });
+                });
+        }
+
+        // update only the changed region on the GPU, if the texture
+        // is not uploaded yet, the entire texture will be uploaded
+        // when required
+        if let Some(gl_tex) = self.gl_tex {
+            // flip the region vertically, same as `self.pixels`
+            let region_pixels: Vec<(u8, u8, u8, u8)> = delta_image_pixels
+                .chunks(delta_image_width)
+                .rev()
+                .flat_map(|row| {
+                    row.iter()
+                        .map(|pixel| (pixel.r(), pixel.g(), pixel.b(), pixel.a()))
+                })
+                .collect();
+
+            // OpenGL has (0, 0) as the bottom left
+            let gl_pos_y = self.height - start_pos[1] - delta.image.height();
+
+            unsafe {
+                gl::PixelStorei(
+                    gl::UNPACK_ALIGNMENT,
+                    Self::unpack_alignment(4 * delta_image_width),
+                );
+
+                gl::BindTexture(gl::TEXTURE_2D, gl_tex);
+
+                gl::TexSubImage2D(
+                    gl::TEXTURE_2D,
+                    0,
+                    start_pos[0].try_into().unwrap(),
+                    gl_pos_y.try_into().unwrap(),
+                    delta.image.width().try_into().unwrap(),
+                    delta.image.height().try_into().unwrap(),
+                    gl::RGBA,
+                    gl::UNSIGNED_BYTE,
+                    region_pixels.as_ptr() as *const gl::types::GLvoid,
+                );
+            }
+
+            self.generate_mipmaps_if_needed();
+        }
+    }
+
+    /// Create a [`TextureRGBA8`] from an [`egui::ColorImage`].
+    pub fn from_color_image(
+        image: &egui::ColorImage,
+        texture_options: egui::TextureOptions,
+    ) -> Self {
+        let [width, height] = image.size;
+        Self::from_pixels(
+            width,
+            height,
+            image
+                .pixels
+                // a zero width image has no rows
+                .chunks(width.max(1))
+                .rev()
+                .flat_map(|row| {
+                    row.iter()
+                        .map(|pixel| (pixel.r(), pixel.g(), pixel.b(), pixel.a()))
+                })
+                .collect(),
+            texture_options,
+        )
     }
 
+    /// Create a [`TextureRGBA8`] from straight (not premultiplied)
+    /// RGBA bytes stored from top left row wise (the layout used by
+    /// most image libraries). The pixels are premultiplied, as egui
+    /// expects.
+    pub fn from_rgba_bytes(
+        width: usize,
+        height: usize,
+        bytes: &[u8],
+        texture_options: egui::TextureOptions,
+    ) -> Self {
+        assert_eq!(bytes.len(), width * height * 4);
+        Self::from_pixels(
+            width,
+            height,
+            flip_rgba_rows(bytes, width)
+                .map(|[r, g, b, a]| {
+                    let pixel = egui::Color32::from_rgba_unmultiplied(r, g, b, a);
+                    (pixel.r(), pixel.g(), pixel.b(), pixel.a())
+                })
+                .collect(),
+            texture_options,
+        )
+    }
+
+    /// Create a [`TextureRGBA8`] from an [`image::RgbaImage`].
+    #[cfg(feature = "image")]
+    pub fn from_rgba_image(
+        image: &image::RgbaImage,
+        texture_options: egui::TextureOptions,
+    ) -> Self {
+        Self::from_rgba_bytes(
+            image.width().try_into().unwrap(),
+            image.height().try_into().unwrap(),
+            image.as_raw(),
+            texture_options,
+        )
+    }
+
+    /// Read back the texture from the GPU as straight (not
+    /// premultiplied) RGBA bytes stored from top left row wise, the
+    /// inverse of [`Self::from_rgba_bytes()`]. The texture is sent to
+    /// the GPU if not done so already.
+    pub fn to_rgba_bytes(&mut self) -> Vec<u8> {
+        let gl_tex = self.get_gl_tex();
+
+        let mut pixels = vec![0_u8; self.width * self.height * 4];
+        unsafe {
+            // rows of RGBA8 pixels are always 4 byte aligned
+            gl::PixelStorei(gl::PACK_ALIGNMENT, 4);
+
+            gl::BindTexture(gl::TEXTURE_2D, gl_tex);
+
+            gl::GetTexImage(
+                gl::TEXTURE_2D,
+                0,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                pixels.as_mut_ptr() as *mut gl::types::GLvoid,
+            );
+        }
+
+        // OpenGL returns the rows from the bottom left, flip them
+        unmultiply_rgba_bytes(&pixels, self.width)
+    }
+
+    /// Read back the texture from the GPU as an
+    /// [`egui::ColorImage`]. The texture is sent to the GPU if not
+    /// done so already.
+    pub fn to_color_image(&mut self) -> egui::ColorImage {
+        egui::ColorImage::from_rgba_unmultiplied([self.width, self.height], &self.to_rgba_bytes())
+    }
+
+    /// Read back the texture from the GPU as an
+    /// [`image::RgbaImage`]. The texture is sent to the GPU if not
+    /// done so already.
+    #[cfg(feature = "image")]
+    pub fn to_rgba_image(&mut self) -> image::RgbaImage {
+        image::RgbaImage::from_raw(
+            self.width.try_into().unwrap(),
+            self.height.try_into().unwrap(),
+            self.to_rgba_bytes(),
+        )
+        .unwrap()
+    }
+
+    /// Read back the texture from the GPU and save it as a PNG file
+    /// at `path`.
+    #[cfg(feature = "image")]
+    pub fn save_png(&mut self, path: impl AsRef<std::path::Path>) -> image::ImageResult<()> {
+        self.to_rgba_image()
+            .save_with_format(path, image::ImageFormat::Png)
+    }
+}
+
+impl<F: PixelFormat> Texture<F> {
     /// # Safety
     ///
     /// There is no way to generate [`Texture`] without automatically
@@ -150,8 +472,70 @@ impl TextureRGBA8 {
         assert!(self.gl_tex.is_none());
 
         self.gl_tex = Some(Self::gen_gl_texture(&self.texture_options));
+        self.gl_context = util::get_current_gl_context();
 
         self.new_texture_to_gl();
+
+        if self.pixel_retention != PixelRetention::KeepCpuCopy {
+            self.pixels = None;
+        }
+    }
+
+    /// Allocate the texture on the GPU without uploading the
+    /// pixels, the returned [`TextureUpload`] uploads the pixels in
+    /// chunks (see [`crate::texture_upload::TextureUploadQueue`]) so
+    /// that large textures do not stall the frame.
+    ///
+    /// The contents of the texture are undefined until the upload is
+    /// complete. Returns [`None`] if the texture is on the GPU
+    /// already, there is nothing to upload then.
+    ///
+    /// # Panics
+    ///
+    /// If the pixels of the texture were released (see
+    /// [`PixelRetention`]) and the texture is not on the GPU, same as
+    /// [`Self::send_to_gpu()`].
+    pub fn send_to_gpu_streamed(&mut self) -> Option<TextureUpload> {
+        if self.gl_tex.is_some() {
+            return None;
+        }
+
+        let pixels = if self.pixel_retention == PixelRetention::KeepCpuCopy {
+            self.pixels.clone()
+        } else {
+            self.pixels.take()
+        }
+        .expect("pixels of the texture were released, cannot upload the texture to the GPU again");
+
+        let gl_tex = Self::gen_gl_texture(&self.texture_options);
+        self.gl_tex = Some(gl_tex);
+        self.gl_context = util::get_current_gl_context();
+
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_2D, gl_tex);
+
+            // allocate the storage only
+            gl::TexImage2D(
+                gl::TEXTURE_2D,
+                0,
+                F::INTERNAL_FORMAT.try_into().unwrap(),
+                self.width.try_into().unwrap(),
+                self.height.try_into().unwrap(),
+                0,
+                F::FORMAT,
+                F::TYPE,
+                std::ptr::null(),
+            );
+        }
+
+        Some(TextureUpload::new::<F>(
+            gl_tex,
+            self.width,
+            self.height,
+            Self::unpack_alignment(std::mem::size_of::<F::Pixel>() * self.width),
+            self.texture_options.mipmap_mode.is_some(),
+            pixels,
+        ))
     }
 
     pub fn activate(&mut self, texture_target: u8) {
@@ -201,40 +585,60 @@ impl TextureRGBA8 {
     }
 
     fn new_texture_to_gl(&self) {
-        assert_eq!(self.pixels.len(), self.width * self.height);
+        let pixels = self.pixels.as_ref().expect(
+            "pixels of the texture were released, cannot upload the texture to the GPU again",
+        );
+        assert_eq!(pixels.len(), self.width * self.height);
 
-        let pixel_size = 4;
-
-        // set the row alignment based on the pixel size and the
-        // number of rows
-        let row_length_in_bytes = pixel_size * self.width;
-        let unpack_alignment = if row_length_in_bytes % 8 == 0 {
-            8
-        } else if row_length_in_bytes % 4 == 0 {
-            4
-        } else if row_length_in_bytes % 2 == 0 {
-            2
-        } else {
-            1
-        };
+        let pixel_size = std::mem::size_of::<F::Pixel>();
 
         unsafe {
-            gl::PixelStorei(gl::UNPACK_ALIGNMENT, unpack_alignment);
+            gl::PixelStorei(
+                gl::UNPACK_ALIGNMENT,
+                Self::unpack_alignment(pixel_size * self.width),
+            );
 
             gl::BindTexture(gl::TEXTURE_2D, self.gl_tex.unwrap());
 
             gl::TexImage2D(
                 gl::TEXTURE_2D,
                 0,
-                gl::RGBA8.try_into().unwrap(),
+                F::INTERNAL_FORMAT.try_into().unwrap(),
                 self.width.try_into().unwrap(),
                 self.height.try_into().unwrap(),
                 0,
-                gl::RGBA,
-                gl::UNSIGNED_BYTE,
-                self.pixels.as_ptr() as *const gl::types::GLvoid,
+                F::FORMAT,
+                F::TYPE,
+                pixels.as_ptr() as *const gl::types::GLvoid,
             )
         }
+
+        self.generate_mipmaps_if_needed();
+    }
+
+    /// Generate the mipmaps of the texture currently bound to
+    /// `gl::TEXTURE_2D` if the [`egui::TextureOptions`] of the
+    /// texture request them.
+    fn generate_mipmaps_if_needed(&self) {
+        if self.texture_options.mipmap_mode.is_some() {
+            unsafe {
+                gl::GenerateMipmap(gl::TEXTURE_2D);
+            }
+        }
+    }
+
+    /// Get the unpack alignment based on the length of a row of
+    /// pixels in bytes.
+    fn unpack_alignment(row_length_in_bytes: usize) -> gl::types::GLint {
+        if row_length_in_bytes % 8 == 0 {
+            8
+        } else if row_length_in_bytes % 4 == 0 {
+            4
+        } else if row_length_in_bytes % 2 == 0 {
+            2
+        } else {
+            1
+        }
     }
 
     fn gen_gl_texture(texture_options: &egui::TextureOptions) -> gl::types::GLuint {
@@ -247,6 +651,18 @@ impl TextureRGBA8 {
         unsafe {
             gl::BindTexture(gl::TEXTURE_2D, gl_tex);
 
+            let swizzle = F::SWIZZLE.map(|channel| channel.try_into().unwrap());
+            gl::TexParameteriv(gl::TEXTURE_2D, gl::TEXTURE_SWIZZLE_RGBA, swizzle.as_ptr());
+        }
+        Self::set_gl_texture_options(texture_options);
+
+        gl_tex
+    }
+
+    /// Set the [`egui::TextureOptions`] of the texture currently
+    /// bound to `gl::TEXTURE_2D`.
+    fn set_gl_texture_options(texture_options: &egui::TextureOptions) {
+        unsafe {
             // wrapping method
             gl::TexParameteri(
                 gl::TEXTURE_2D,
@@ -263,7 +679,11 @@ impl TextureRGBA8 {
             gl::TexParameteri(
                 gl::TEXTURE_2D,
                 gl::TEXTURE_MIN_FILTER,
-                texture_options.minification.to_gl().try_into().unwrap(),
+                texture_options
+                    .minification
+                    .to_gl_min_filter(texture_options.mipmap_mode)
+                    .try_into()
+                    .unwrap(),
             );
             gl::TexParameteri(
                 gl::TEXTURE_2D,
@@ -271,8 +691,6 @@ impl TextureRGBA8 {
                 texture_options.magnification.to_gl().try_into().unwrap(),
             );
         }
-
-        gl_tex
     }
 
     /// Get OpenGL texture name (GLuint) of the current texture, send
@@ -292,6 +710,74 @@ impl TextureRGBA8 {
         self.height
     }
 
+    /// Get the [`PixelRetention`] policy of the texture.
+    pub fn get_pixel_retention(&self) -> PixelRetention {
+        self.pixel_retention
+    }
+
+    /// Set the [`PixelRetention`] policy of the texture.
+    ///
+    /// For [`PixelRetention::GpuOnly`], the texture is uploaded to
+    /// the GPU immediately if not done so already. Pixels that were
+    /// released already cannot be retrieved by changing the policy.
+    pub fn set_pixel_retention(&mut self, pixel_retention: PixelRetention) {
+        self.pixel_retention = pixel_retention;
+        match pixel_retention {
+            PixelRetention::GpuOnly => {
+                if self.gl_tex.is_none() {
+                    unsafe { self.send_to_gpu() };
+                }
+                self.pixels = None;
+            }
+            PixelRetention::KeepCpuCopy => {}
+            PixelRetention::KeepUntilUploaded => {
+                if self.gl_tex.is_some() {
+                    self.pixels = None;
+                }
+            }
+        }
+    }
+
+    /// Get the CPU side copy of the pixels (stored from bottom left
+    /// row wise) if they are retained, see [`PixelRetention`].
+    pub fn get_pixels(&self) -> Option<&[F::Pixel]> {
+        self.pixels.as_deref()
+    }
+
+    /// Get the [`egui::TextureOptions`] of the texture.
+    pub fn get_texture_options(&self) -> &egui::TextureOptions {
+        &self.texture_options
+    }
+
+    /// Get the number of bytes used by the texture on the GPU
+    /// (including the mipmaps), 0 if it is not uploaded.
+    pub fn get_gpu_bytes(&self) -> usize {
+        if self.gl_tex.is_none() {
+            return 0;
+        }
+
+        gpu_bytes(
+            self.width,
+            self.height,
+            F::GPU_BYTES_PER_PIXEL,
+            self.texture_options.mipmap_mode.is_some(),
+        )
+    }
+
+    /// Get the number of bytes used by the CPU side copy of the
+    /// pixels, see [`PixelRetention`].
+    pub fn get_cpu_bytes(&self) -> usize {
+        self.pixels
+            .as_ref()
+            .map_or(0, |pixels| pixels.len() * std::mem::size_of::<F::Pixel>())
+    }
+
+    /// Delete the texture from the GPU.
+    ///
+    /// # Note
+    ///
+    /// If the pixels are not retained (see [`PixelRetention`]), the
+    /// texture cannot be uploaded to the GPU again.
     pub fn cleanup_opengl(&mut self) {
         unsafe {
             gl::DeleteTextures(1, &self.gl_tex.unwrap());
@@ -300,18 +786,132 @@ impl TextureRGBA8 {
     }
 }
 
-impl Drop for TextureRGBA8 {
+impl<F: PixelFormat> Drop for Texture<F> {
     fn drop(&mut self) {
         if self.gl_tex.is_some() {
+            let gl_context = util::get_current_gl_context();
+            if gl_context == 0 {
+                // deleting without a current context is undefined,
+                // leak the texture instead
+                #[cfg(feature = "tracing")]
+                tracing::error!(
+                    "texture {} dropped without a current OpenGL context, leaking it",
+                    self.gl_tex.unwrap()
+                );
+                return;
+            }
+
+            #[cfg(feature = "tracing")]
+            if gl_context != self.gl_context {
+                tracing::warn!(
+                    "texture {} dropped with a different OpenGL context current than the one \
+                     it was created with, the contexts must share objects",
+                    self.gl_tex.unwrap()
+                );
+            }
+
             self.cleanup_opengl();
         }
     }
 }
 
+/// A texture that lives on the GPU, allows [`Texture`]s of any
+/// [`PixelFormat`] to be used as a [`UserTexture`].
+pub trait GLTexture {
+    /// Get OpenGL texture name (GLuint) of the texture, send texture
+    /// to GPU if not done so already.
+    fn get_gl_tex(&mut self) -> gl::types::GLuint;
+
+    /// Get the number of bytes used by the texture on the GPU, used
+    /// for the texture statistics.
+    fn get_gpu_bytes(&self) -> usize {
+        0
+    }
+
+    /// Get the number of bytes used by the texture on the CPU, used
+    /// for the texture statistics.
+    fn get_cpu_bytes(&self) -> usize {
+        0
+    }
+}
+
+impl<F: PixelFormat> GLTexture for Texture<F> {
+    fn get_gl_tex(&mut self) -> gl::types::GLuint {
+        Texture::get_gl_tex(self)
+    }
+
+    fn get_gpu_bytes(&self) -> usize {
+        Texture::get_gpu_bytes(self)
+    }
+
+    fn get_cpu_bytes(&self) -> usize {
+        Texture::get_cpu_bytes(self)
+    }
+}
+
+/// Texture registered with the backend to be used as an
+/// [`egui::TextureId::User`].
+pub enum UserTexture {
+    /// Texture owned by the backend, deleted when freed.
+    Owned(Box<dyn GLTexture>),
+    /// Raw OpenGL texture name, the lifetime of the texture is
+    /// managed by the caller.
+    Raw(gl::types::GLuint),
+}
+
+impl UserTexture {
+    /// Get OpenGL texture name (GLuint) of the texture.
+    ///
+    /// Returns [`None`] if the texture is not a valid OpenGL texture
+    /// (for example, a raw texture that was deleted by the caller).
+    pub fn get_gl_tex(&mut self) -> Option<gl::types::GLuint> {
+        match self {
+            UserTexture::Owned(texture) => Some(texture.get_gl_tex()),
+            UserTexture::Raw(gl_tex) => {
+                (unsafe { gl::IsTexture(*gl_tex) } == gl::TRUE).then_some(*gl_tex)
+            }
+        }
+    }
+    /// Get the number of bytes used by the texture on the GPU.
+    ///
+    /// Raw textures are managed by the caller and are reported as
+    /// 0 bytes.
+    pub fn get_gpu_bytes(&self) -> usize {
+        match self {
+            UserTexture::Owned(texture) => texture.get_gpu_bytes(),
+            UserTexture::Raw(_) => 0,
+        }
+    }
+
+    /// Get the number of bytes used by the texture on the CPU.
+    pub fn get_cpu_bytes(&self) -> usize {
+        match self {
+            UserTexture::Owned(texture) => texture.get_cpu_bytes(),
+            UserTexture::Raw(_) => 0,
+        }
+    }
+}
+
+impl<F: PixelFormat + 'static> From<Texture<F>> for UserTexture {
+    fn from(texture: Texture<F>) -> Self {
+        Self::Owned(Box::new(texture))
+    }
+}
+
+impl From<gl::types::GLuint> for UserTexture {
+    fn from(gl_tex: gl::types::GLuint) -> Self {
+        Self::Raw(gl_tex)
+    }
+}
+
 /// Extention to [`egui::TextureFilter`].
 pub trait EguiTextureFilterExtend {
     /// Convert to equivalent texture filter parameter to OpenGL.
     fn to_gl(&self) -> gl::types::GLenum;
+
+    /// Convert to equivalent texture minification filter parameter
+    /// to OpenGL taking into account the filter between the mipmaps.
+    fn to_gl_min_filter(&self, mipmap_mode: Option<egui::TextureFilter>) -> gl::types::GLenum;
 }
 
 impl EguiTextureFilterExtend for egui::TextureFilter {
@@ -321,6 +921,24 @@ impl EguiTextureFilterExtend for egui::TextureFilter {
             egui::TextureFilter::Linear => gl::LINEAR,
         }
     }
+
+    fn to_gl_min_filter(&self, mipmap_mode: Option<egui::TextureFilter>) -> gl::types::GLenum {
+        match (self, mipmap_mode) {
+            (_, None) => self.to_gl(),
+            (egui::TextureFilter::Nearest, Some(egui::TextureFilter::Nearest)) => {
+                gl::NEAREST_MIPMAP_NEAREST
+            }
+            (egui::TextureFilter::Nearest, Some(egui::TextureFilter::Linear)) => {
+                gl::NEAREST_MIPMAP_LINEAR
+            }
+            (egui::TextureFilter::Linear, Some(egui::TextureFilter::Nearest)) => {
+                gl::LINEAR_MIPMAP_NEAREST
+            }
+            (egui::TextureFilter::Linear, Some(egui::TextureFilter::Linear)) => {
+                gl::LINEAR_MIPMAP_LINEAR
+            }
+        }
+    }
 }
 
 /// Extention to [`egui::TextureWrapMode`].
@@ -338,3 +956,157 @@ impl EguiTextureWrapModeExtend for egui::TextureWrapMode {
         }
     }
 }
+
+/// Get the number of bytes used on the GPU by a `width` x `height`
+/// texture with `bytes_per_pixel`, including the mipmaps if any.
+fn gpu_bytes(width: usize, height: usize, bytes_per_pixel: usize, mipmaps: bool) -> usize {
+    if !mipmaps {
+        return width * height * bytes_per_pixel;
+    }
+
+    let (mut width, mut height) = (width, height);
+    let mut bytes = width * height * bytes_per_pixel;
+    while width > 1 || height > 1 {
+        width = (width / 2).max(1);
+        height = (height / 2).max(1);
+        bytes += width * height * bytes_per_pixel;
+    }
+    bytes
+}
+
+/// Iterate over the RGBA pixels of `bytes` with the rows of `width`
+/// pixels in the reverse order, to convert between rows stored from
+/// top left and from bottom left.
+fn flip_rgba_rows(bytes: &[u8], width: usize) -> impl Iterator<Item = [u8; 4]> + '_ {
+    bytes
+        // a zero width image has no rows
+        .chunks((width * 4).max(1))
+        .rev()
+        .flat_map(|row| {
+            row.chunks_exact(4)
+                .map(|pixel| [pixel[0], pixel[1], pixel[2], pixel[3]])
+        })
+}
+
+/// Convert the premultiplied RGBA `bytes` with rows of `width`
+/// pixels stored from bottom left to straight RGBA bytes stored from
+/// top left.
+fn unmultiply_rgba_bytes(bytes: &[u8], width: usize) -> Vec<u8> {
+    flip_rgba_rows(bytes, width)
+        .flat_map(|[r, g, b, a]| {
+            egui::Color32::from_rgba_premultiplied(r, g, b, a).to_srgba_unmultiplied()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pixel_retention_defaults() {
+        let texture =
+            TextureRGBA8::from_pixels(2, 1, vec![(0, 0, 0, 0); 2], egui::TextureOptions::LINEAR);
+        assert_eq!(texture.get_pixel_retention(), PixelRetention::KeepCpuCopy);
+
+        let delta = egui::epaint::ImageDelta::full(
+            egui::ColorImage::new([2, 1], egui::Color32::RED),
+            egui::TextureOptions::LINEAR,
+        );
+        let texture = TextureRGBA8::from_egui(&delta).unwrap();
+        assert_eq!(
+            texture.get_pixel_retention(),
+            PixelRetention::KeepUntilUploaded
+        );
+    }
+
+    /// Get the bytes of the pixels of `texture`.
+    fn pixel_bytes(texture: &TextureRGBA8) -> Vec<u8> {
+        texture
+            .get_pixels()
+            .unwrap()
+            .iter()
+            .flat_map(|&(r, g, b, a)| [r, g, b, a])
+            .collect()
+    }
+
+    #[test]
+    fn rgba_bytes_are_flipped_and_premultiplied() {
+        #[rustfmt::skip]
+        let bytes = [
+            255, 0, 0, 255,  0, 255, 0, 255,
+            0, 0, 255, 255,  255, 255, 255, 0,
+        ];
+        let texture = TextureRGBA8::from_rgba_bytes(2, 2, &bytes, egui::TextureOptions::LINEAR);
+        #[rustfmt::skip]
+        assert_eq!(
+            pixel_bytes(&texture),
+            [
+                0, 0, 255, 255,  0, 0, 0, 0,
+                255, 0, 0, 255,  0, 255, 0, 255,
+            ]
+        );
+    }
+
+    #[test]
+    fn rgba_bytes_round_trip() {
+        let bytes = [
+            [10, 20, 30, 255],
+            [0, 0, 0, 0],
+            [255, 255, 255, 128],
+            [200, 100, 50, 255],
+            [255, 0, 0, 64],
+            [0, 0, 0, 255],
+        ]
+        .concat();
+        let texture = TextureRGBA8::from_rgba_bytes(3, 2, &bytes, egui::TextureOptions::LINEAR);
+        assert_eq!(unmultiply_rgba_bytes(&pixel_bytes(&texture), 3), bytes);
+    }
+
+    #[test]
+    fn color_image_round_trip() {
+        let image = egui::ColorImage {
+            size: [2, 2],
+            pixels: vec![
+                egui::Color32::RED,
+                egui::Color32::TRANSPARENT,
+                egui::Color32::from_rgba_unmultiplied(255, 255, 255, 128),
+                egui::Color32::from_rgba_unmultiplied(0, 128, 255, 200),
+            ],
+        };
+        let texture = TextureRGBA8::from_color_image(&image, egui::TextureOptions::LINEAR);
+        let bytes = unmultiply_rgba_bytes(&pixel_bytes(&texture), 2);
+        assert_eq!(
+            egui::ColorImage::from_rgba_unmultiplied([2, 2], &bytes),
+            image
+        );
+    }
+
+    #[test]
+    fn zero_width_images() {
+        let texture = TextureRGBA8::from_rgba_bytes(0, 3, &[], egui::TextureOptions::LINEAR);
+        assert_eq!(texture.get_pixels(), Some(&[][..]));
+        assert_eq!(unmultiply_rgba_bytes(&[], 0), Vec::<u8>::new());
+
+        let image = egui::ColorImage::new([0, 3], egui::Color32::RED);
+        let texture = TextureRGBA8::from_color_image(&image, egui::TextureOptions::LINEAR);
+        assert_eq!(texture.get_pixels(), Some(&[][..]));
+    }
+
+    #[test]
+    fn gpu_bytes_of_formats() {
+        assert_eq!(gpu_bytes(4, 4, RGBA8::GPU_BYTES_PER_PIXEL, false), 64);
+        // half floats are given as f32 but stored as 2 bytes
+        assert_eq!(gpu_bytes(4, 4, RGBA16F::GPU_BYTES_PER_PIXEL, false), 128);
+        assert_eq!(gpu_bytes(4, 4, RGBA32F::GPU_BYTES_PER_PIXEL, false), 256);
+    }
+
+    #[test]
+    fn gpu_bytes_of_mipmaps() {
+        // 4x4 + 2x2 + 1x1
+        assert_eq!(gpu_bytes(4, 4, RGBA16F::GPU_BYTES_PER_PIXEL, true), 21 * 8);
+        // 8x2 + 4x1 + 2x1 + 1x1
+        assert_eq!(gpu_bytes(8, 2, R8::GPU_BYTES_PER_PIXEL, true), 23);
+        assert_eq!(gpu_bytes(1, 1, RGBA8::GPU_BYTES_PER_PIXEL, true), 4);
+    }
+}
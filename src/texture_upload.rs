@@ -0,0 +1,367 @@
+use std::{collections::VecDeque, convert::TryInto, ops::Range};
+
+use crate::texture::PixelFormat;
+
+/// Default number of bytes uploaded per frame by the
+/// [`TextureUploadQueue`].
+pub const DEFAULT_UPLOAD_BYTES_PER_FRAME: usize = 8 * 1024 * 1024;
+
+/// Pixel data that can be viewed as bytes.
+pub trait PixelBytes {
+    /// Get the pixel data as bytes.
+    fn as_bytes(&self) -> &[u8];
+}
+
+impl<P: Copy> PixelBytes for Vec<P> {
+    fn as_bytes(&self) -> &[u8] {
+        // SAFETY: pixels are plain data (integer and float
+        // components), the same assumption is made when the pixels
+        // are passed to OpenGL directly
+        unsafe {
+            std::slice::from_raw_parts(
+                self.as_ptr() as *const u8,
+                self.len() * std::mem::size_of::<P>(),
+            )
+        }
+    }
+}
+
+/// An upload of the pixels of a texture to the GPU that is done in
+/// chunks of rows, see [`crate::Texture::send_to_gpu_streamed()`].
+pub struct TextureUpload {
+    /// OpenGL texture the pixels are uploaded to, its storage must be
+    /// allocated already.
+    gl_tex: gl::types::GLuint,
+    /// Width of the texture.
+    width: usize,
+    /// Height of the texture.
+    height: usize,
+    /// OpenGL format of the pixel data.
+    format: gl::types::GLenum,
+    /// OpenGL type of the components of the pixel data.
+    r#type: gl::types::GLenum,
+    /// Unpack alignment of the rows of pixels.
+    unpack_alignment: gl::types::GLint,
+    /// Should mipmaps be generated once the upload is complete.
+    generate_mipmaps: bool,
+
+    /// Pixels of the image stored from bottom left row wise.
+    pixels: Box<dyn PixelBytes>,
+    /// Number of rows uploaded so far.
+    rows_uploaded: usize,
+}
+
+impl TextureUpload {
+    /// Create a new [`TextureUpload`] of the `pixels` (stored from
+    /// bottom left row wise) to `gl_tex` whose storage must be
+    /// allocated already.
+    pub fn new<F: PixelFormat>(
+        gl_tex: gl::types::GLuint,
+        width: usize,
+        height: usize,
+        unpack_alignment: gl::types::GLint,
+        generate_mipmaps: bool,
+        pixels: Vec<F::Pixel>,
+    ) -> Self {
+        assert_eq!(pixels.len(), width * height);
+        Self {
+            gl_tex,
+            width,
+            height,
+            format: F::FORMAT,
+            r#type: F::TYPE,
+            unpack_alignment,
+            generate_mipmaps,
+            pixels: Box::new(pixels),
+            rows_uploaded: 0,
+        }
+    }
+
+    /// Size of a row of pixels in bytes.
+    fn row_size(&self) -> usize {
+        self.pixels.as_bytes().len() / self.height
+    }
+
+    /// Is the upload complete.
+    pub fn is_complete(&self) -> bool {
+        // nothing to upload for an empty texture
+        self.rows_uploaded == self.height || self.width == 0
+    }
+
+    /// Take at most `max_bytes` bytes (at least one row) of the
+    /// remaining rows to upload next.
+    ///
+    /// Returns the range of the rows taken.
+    fn take_rows(&mut self, max_bytes: usize) -> Range<usize> {
+        let rows_remaining = self.height - self.rows_uploaded;
+        let rows = max_bytes
+            .checked_div(self.row_size())
+            .unwrap_or(rows_remaining)
+            .clamp(1, rows_remaining);
+        let start = self.rows_uploaded;
+        self.rows_uploaded += rows;
+        start..self.rows_uploaded
+    }
+
+    /// Upload at most `max_bytes` bytes (at least one row) of the
+    /// remaining pixels through the pixel buffer object `pbo`.
+    ///
+    /// Returns the number of bytes uploaded.
+    fn upload(&mut self, pbo: gl::types::GLuint, max_bytes: usize) -> usize {
+        let rows = self.take_rows(max_bytes);
+        let row_size = self.row_size();
+        let bytes = &self.pixels.as_bytes()[(rows.start * row_size)..(rows.end * row_size)];
+
+        unsafe {
+            gl::BindBuffer(gl::PIXEL_UNPACK_BUFFER, pbo);
+            // orphan the previous storage so that the upload does not
+            // wait on the previous chunk
+            gl::BufferData(
+                gl::PIXEL_UNPACK_BUFFER,
+                bytes.len().try_into().unwrap(),
+                std::ptr::null(),
+                gl::STREAM_DRAW,
+            );
+            let data = gl::MapBufferRange(
+                gl::PIXEL_UNPACK_BUFFER,
+                0,
+                bytes.len().try_into().unwrap(),
+                gl::MAP_WRITE_BIT | gl::MAP_INVALIDATE_BUFFER_BIT,
+            ) as *mut u8;
+            assert_ne!(data, std::ptr::null_mut());
+            std::ptr::copy_nonoverlapping(bytes.as_ptr(), data, bytes.len());
+            gl::UnmapBuffer(gl::PIXEL_UNPACK_BUFFER);
+
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, self.unpack_alignment);
+            gl::BindTexture(gl::TEXTURE_2D, self.gl_tex);
+
+            // rows are stored from the bottom left, same as OpenGL
+            gl::TexSubImage2D(
+                gl::TEXTURE_2D,
+                0,
+                0,
+                rows.start.try_into().unwrap(),
+                self.width.try_into().unwrap(),
+                rows.len().try_into().unwrap(),
+                self.format,
+                self.r#type,
+                std::ptr::null(),
+            );
+
+            // must unbind, otherwise any other pixel transfer would
+            // read from the pixel buffer object
+            gl::BindBuffer(gl::PIXEL_UNPACK_BUFFER, 0);
+        }
+
+        if self.is_complete() && self.generate_mipmaps {
+            unsafe {
+                gl::GenerateMipmap(gl::TEXTURE_2D);
+            }
+        }
+
+        bytes.len()
+    }
+}
+
+/// Queue of [`TextureUpload`]s that are spread across frames under
+/// a per frame byte budget using a pixel buffer object.
+pub struct TextureUploadQueue<Id> {
+    /// Pixel buffer object used for the uploads.
+    pbo: gl::types::GLuint,
+    /// Pending uploads.
+    uploads: VecDeque<(Id, TextureUpload)>,
+    /// Maximum number of bytes uploaded per frame.
+    bytes_per_frame: usize,
+}
+
+impl<Id: PartialEq + Copy> TextureUploadQueue<Id> {
+    /// Create a new empty [`TextureUploadQueue`].
+    pub fn new() -> Self {
+        let mut pbo = 0;
+        unsafe {
+            gl::GenBuffers(1, &mut pbo);
+        }
+        assert_ne!(pbo, 0);
+
+        Self {
+            pbo,
+            uploads: VecDeque::new(),
+            bytes_per_frame: DEFAULT_UPLOAD_BYTES_PER_FRAME,
+        }
+    }
+
+    /// Set the maximum number of bytes uploaded per frame. At least
+    /// one row of a texture is uploaded per frame irrespective of
+    /// the budget.
+    pub fn set_bytes_per_frame(&mut self, bytes_per_frame: usize) {
+        self.bytes_per_frame = bytes_per_frame;
+    }
+
+    /// Push an upload for the texture `id` to the queue.
+    pub fn push(&mut self, id: Id, upload: TextureUpload) {
+        if !upload.is_complete() {
+            self.uploads.push_back((id, upload));
+        }
+    }
+
+    /// Cancel the upload of the texture `id` if it is pending. Must
+    /// be called before the OpenGL texture is deleted.
+    pub fn cancel(&mut self, id: Id) {
+        self.uploads.retain(|(upload_id, _)| *upload_id != id);
+    }
+
+    /// Is the upload of the texture `id` pending.
+    pub fn is_pending(&self, id: Id) -> bool {
+        self.uploads.iter().any(|(upload_id, _)| *upload_id == id)
+    }
+
+    /// Get the textures whose upload is pending.
+    pub fn pending(&self) -> impl Iterator<Item = Id> + '_ {
+        self.uploads.iter().map(|(id, _)| *id)
+    }
+
+    /// Upload pending textures for this frame, within the per frame
+    /// byte budget.
+    pub fn process(&mut self) {
+        let pbo = self.pbo;
+        process_uploads(&mut self.uploads, self.bytes_per_frame, |upload, budget| {
+            upload.upload(pbo, budget)
+        });
+    }
+}
+
+/// Upload the front of `uploads` with `upload` (given the upload and
+/// the remaining budget, returns the number of bytes uploaded) until
+/// `bytes_per_frame` bytes are uploaded, removing the completed
+/// uploads. The front is uploaded at least once so that the uploads
+/// progress irrespective of the budget.
+fn process_uploads<Id>(
+    uploads: &mut VecDeque<(Id, TextureUpload)>,
+    bytes_per_frame: usize,
+    mut upload: impl FnMut(&mut TextureUpload, usize) -> usize,
+) {
+    let mut budget = bytes_per_frame;
+    while let Some((_, front)) = uploads.front_mut() {
+        let uploaded = upload(front, budget);
+        budget = budget.saturating_sub(uploaded);
+
+        if front.is_complete() {
+            uploads.pop_front();
+        }
+
+        if budget == 0 {
+            break;
+        }
+    }
+}
+
+impl<Id: PartialEq + Copy> Default for TextureUploadQueue<Id> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Id> Drop for TextureUploadQueue<Id> {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteBuffers(1, &self.pbo);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::texture::{R8, RGBA8};
+
+    /// Upload of a `width` x `height` texture of the format `F`.
+    fn texture_upload<F: PixelFormat>(width: usize, height: usize) -> TextureUpload
+    where
+        F::Pixel: Default,
+    {
+        TextureUpload::new::<F>(
+            0,
+            width,
+            height,
+            1,
+            false,
+            vec![F::Pixel::default(); width * height],
+        )
+    }
+
+    /// Process `uploads` without OpenGL, returns the rows taken.
+    fn process(
+        uploads: &mut VecDeque<(usize, TextureUpload)>,
+        bytes_per_frame: usize,
+    ) -> Vec<Range<usize>> {
+        let mut taken = Vec::new();
+        process_uploads(uploads, bytes_per_frame, |upload, budget| {
+            let rows = upload.take_rows(budget);
+            let bytes = rows.len() * upload.row_size();
+            taken.push(rows);
+            bytes
+        });
+        taken
+    }
+
+    /// Get the ids of the pending `uploads`.
+    fn pending(uploads: &VecDeque<(usize, TextureUpload)>) -> Vec<usize> {
+        uploads.iter().map(|(id, _)| *id).collect()
+    }
+
+    #[test]
+    fn take_rows_within_budget() {
+        // 16 bytes per row
+        let mut upload = texture_upload::<RGBA8>(4, 8);
+        assert_eq!(upload.row_size(), 16);
+
+        assert_eq!(upload.take_rows(40), 0..2);
+        // at least one row irrespective of the budget
+        assert_eq!(upload.take_rows(0), 2..3);
+        assert_eq!(upload.take_rows(10), 3..4);
+        assert!(!upload.is_complete());
+        // never more than the remaining rows
+        assert_eq!(upload.take_rows(usize::MAX), 4..8);
+        assert!(upload.is_complete());
+    }
+
+    #[test]
+    fn empty_upload_is_complete() {
+        assert!(texture_upload::<RGBA8>(0, 4).is_complete());
+        assert!(texture_upload::<RGBA8>(4, 0).is_complete());
+        assert!(!texture_upload::<RGBA8>(1, 1).is_complete());
+    }
+
+    #[test]
+    fn process_spreads_uploads_across_frames() {
+        let mut uploads = VecDeque::new();
+        // 3 bytes per row
+        uploads.push_back((0, texture_upload::<R8>(3, 4)));
+        // 8 bytes per row
+        uploads.push_back((1, texture_upload::<RGBA8>(2, 2)));
+
+        assert_eq!(process(&mut uploads, 7), vec![0..2, 2..3]);
+        assert_eq!(pending(&uploads), vec![0, 1]);
+
+        // the rest of the budget goes to the next upload
+        assert_eq!(process(&mut uploads, 11), vec![3..4, 0..1]);
+        assert_eq!(pending(&uploads), vec![1]);
+
+        assert_eq!(process(&mut uploads, 100), vec![1..2]);
+        assert!(uploads.is_empty());
+
+        assert_eq!(process(&mut uploads, 100), vec![]);
+    }
+
+    #[test]
+    fn process_with_no_budget_uploads_a_row() {
+        let mut uploads = VecDeque::new();
+        uploads.push_back((0, texture_upload::<RGBA8>(2, 2)));
+
+        assert_eq!(process(&mut uploads, 0), vec![0..1]);
+        assert_eq!(pending(&uploads), vec![0]);
+
+        assert_eq!(process(&mut uploads, 0), vec![1..2]);
+        assert!(uploads.is_empty());
+    }
+}
@@ -0,0 +1,156 @@
+use std::{cell::RefCell, rc::Rc};
+
+/// Opens the URLs of [`egui::OutputCommand::OpenUrl`], see
+/// [`crate::EguiBackend::set_url_opener()`].
+pub trait UrlOpener {
+    /// Open the URL.
+    fn open_url(&mut self, open_url: &egui::OpenUrl);
+}
+
+/// [`UrlOpener`] that opens URLs with the default application of
+/// the system: `xdg-open` on Linux and the BSDs, `open` on macOS and
+/// the `url.dll` protocol handler on Windows.
+///
+/// Only `http`, `https` and `mailto` URLs are opened, other URLs (for
+/// example local files or executables) are refused since the URLs
+/// may come from untrusted content shown by egui.
+///
+/// The URL is always opened in a new tab (or window) of the browser
+/// since none of them can reuse the current tab, thus
+/// [`egui::OpenUrl::new_tab`] being `false` is not honoured.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemUrlOpener;
+
+impl SystemUrlOpener {
+    /// Create a new [`SystemUrlOpener`].
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Can `url` be opened, that is it has an allowed scheme.
+    fn is_allowed(url: &str) -> bool {
+        const ALLOWED_SCHEMES: &[&str] = &["http", "https", "mailto"];
+
+        url.split_once(':').is_some_and(|(scheme, _)| {
+            ALLOWED_SCHEMES
+                .iter()
+                .any(|allowed_scheme| scheme.eq_ignore_ascii_case(allowed_scheme))
+        })
+    }
+
+    /// Get the command that opens `url`.
+    fn command(url: &str) -> std::process::Command {
+        if cfg!(target_os = "windows") {
+            // unlike `cmd /C start`, the URL is not parsed by a shell
+            let mut command = std::process::Command::new("rundll32");
+            command.arg("url.dll,FileProtocolHandler").arg(url);
+            command
+        } else if cfg!(target_os = "macos") {
+            let mut command = std::process::Command::new("open");
+            command.arg(url);
+            command
+        } else {
+            let mut command = std::process::Command::new("xdg-open");
+            command.arg(url);
+            command
+        }
+    }
+}
+
+impl UrlOpener for SystemUrlOpener {
+    fn open_url(&mut self, open_url: &egui::OpenUrl) {
+        if !Self::is_allowed(&open_url.url) {
+            #[cfg(feature = "tracing")]
+            tracing::warn!(
+                "refusing to open {}, its scheme is not allowed",
+                open_url.url
+            );
+            return;
+        }
+
+        match Self::command(&open_url.url).spawn() {
+            Ok(mut child) => {
+                // reap the process once it exits without blocking
+                std::thread::spawn(move || child.wait());
+            }
+            Err(_error) => {
+                #[cfg(feature = "tracing")]
+                tracing::warn!("could not open {}: {}", open_url.url, _error);
+            }
+        }
+    }
+}
+
+/// [`UrlOpener`] that records the URLs instead of opening them,
+/// useful for tests. Clones share the recorded URLs so that a clone
+/// can be kept to inspect the URLs opened through the backend.
+#[derive(Debug, Default, Clone)]
+pub struct RecordingUrlOpener {
+    urls: Rc<RefCell<Vec<egui::OpenUrl>>>,
+}
+
+impl RecordingUrlOpener {
+    /// Create a new [`RecordingUrlOpener`] with no URLs.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the URLs recorded so far.
+    pub fn get_urls(&self) -> Vec<egui::OpenUrl> {
+        self.urls.borrow().clone()
+    }
+
+    /// Take the URLs recorded so far.
+    pub fn take_urls(&self) -> Vec<egui::OpenUrl> {
+        std::mem::take(&mut self.urls.borrow_mut())
+    }
+}
+
+impl UrlOpener for RecordingUrlOpener {
+    fn open_url(&mut self, open_url: &egui::OpenUrl) {
+        self.urls.borrow_mut().push(open_url.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allowed_urls() {
+        [
+            "http://example.com",
+            "https://example.com/a?b=c&d=e",
+            "HTTPS://EXAMPLE.COM",
+            "mailto:someone@example.com",
+        ]
+        .iter()
+        .for_each(|url| assert!(SystemUrlOpener::is_allowed(url), "{}", url));
+    }
+
+    #[test]
+    fn refused_urls() {
+        [
+            "",
+            "example.com",
+            "file:///etc/passwd",
+            "C:\\Windows\\System32\\calc.exe",
+            "javascript:alert(1)",
+            "-a Calculator",
+            "ftp://example.com",
+            "https",
+        ]
+        .iter()
+        .for_each(|url| assert!(!SystemUrlOpener::is_allowed(url), "{}", url));
+    }
+
+    #[test]
+    fn url_is_a_single_argument() {
+        let url = "https://example.com/?a=1&b=2|c^d";
+        let command = SystemUrlOpener::command(url);
+        assert_eq!(command.get_args().last(), Some(std::ffi::OsStr::new(url)));
+        if cfg!(target_os = "windows") {
+            assert_eq!(command.get_program(), "rundll32");
+        }
+    }
+}
@@ -1,3 +1,5 @@
+use std::convert::TryInto;
+
 /// Get offset to struct member, similar to `offset_of` in C/C++
 /// From <https://stackoverflow.com/questions/40310483/how-to-get-pointer-offset-in-bytes/40310851#40310851>
 #[macro_export]
@@ -12,3 +14,38 @@ pub fn str_to_cstr(string: &str) -> &std::ffi::CStr {
     return std::ffi::CStr::from_bytes_with_nul(string.as_bytes())
         .expect("ensure there is a '\\0' at the end of the string");
 }
+
+/// Get the GLFW window whose OpenGL context is current on this
+/// thread as an opaque handle, 0 if no context is current.
+pub fn get_current_gl_context() -> usize {
+    unsafe { glfw::ffi::glfwGetCurrentContext() as usize }
+}
+
+/// Label the OpenGL object `name` of type `identifier` (for example
+/// `gl::TEXTURE`) so that it can be identified in graphics
+/// debuggers. Does nothing if `glObjectLabel` is not supported.
+pub fn set_gl_object_label(identifier: gl::types::GLenum, name: gl::types::GLuint, label: &str) {
+    if !gl::ObjectLabel::is_loaded() {
+        return;
+    }
+    unsafe {
+        gl::ObjectLabel(
+            identifier,
+            name,
+            label.len().try_into().unwrap(),
+            label.as_ptr() as *const gl::types::GLchar,
+        );
+    }
+}
+
+/// Get the number of framebuffer pixels per screen coordinate of the
+/// window.
+pub fn pixels_per_screen_coord(window: &glfw::Window) -> f32 {
+    let (width, _) = window.get_size();
+    let (framebuffer_width, _) = window.get_framebuffer_size();
+    if width == 0 {
+        1.0
+    } else {
+        framebuffer_width as f32 / width as f32
+    }
+}
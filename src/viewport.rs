@@ -0,0 +1,801 @@
+use std::{cell::RefCell, rc::Weak, sync::Arc};
+
+use egui::epaint::ahash::AHashMap;
+
+use glfw::Context;
+
+use crate::{
+    clipboard::ClipboardIntegration,
+    gpu_immediate::GPUImmediate,
+    input::{CommandModifier, Input, ScrollConfig},
+    util::pixels_per_screen_coord,
+};
+
+/// Native window of an egui viewport other than the root viewport.
+pub struct ViewportWindow {
+    /// Immediate mode drawing of the window. Vertex array objects
+    /// are not shared between OpenGL contexts so each window needs
+    /// its own.
+    ///
+    /// Must be dropped before [`Self::window`], see [`Drop`].
+    pub imm: GPUImmediate,
+    /// GLFW window, its OpenGL context is shared with the root
+    /// window.
+    pub window: glfw::PWindow,
+    /// Events of the window.
+    pub events: glfw::GlfwReceiver<(f64, glfw::WindowEvent)>,
+    /// Input of the window.
+    pub input: Input,
+    /// Builder of the window, used to apply changes to the window.
+    pub builder: egui::ViewportBuilder,
+    /// User code that shows the GUI of a deferred viewport.
+    pub viewport_ui_cb: Option<Arc<egui::DeferredViewportUiCallback>>,
+    /// Pixels per point of the last pass of the viewport.
+    pub pixels_per_point: f32,
+    /// State needed to apply the [`egui::ViewportCommand`]s.
+    pub command_state: WindowCommandState,
+}
+
+impl ViewportWindow {
+    /// Create a new [`ViewportWindow`] for `viewport_id` described by
+    /// `builder` whose OpenGL context is shared with `root_window`.
+    ///
+    /// The OpenGL context of the new window is current after this.
+    pub fn new(
+        root_window: &glfw::Window,
+        viewport_id: egui::ViewportId,
+        builder: egui::ViewportBuilder,
+    ) -> Option<Self> {
+        let pixels_per_point = root_window.get_content_scale().0;
+        let screen_coords_per_point = pixels_per_point / pixels_per_screen_coord(root_window);
+
+        let mut glfw = root_window.glfw.clone();
+        glfw.window_hint(glfw::WindowHint::Decorated(
+            builder.decorations.unwrap_or(true),
+        ));
+        glfw.window_hint(glfw::WindowHint::Resizable(
+            builder.resizable.unwrap_or(true),
+        ));
+        glfw.window_hint(glfw::WindowHint::Visible(builder.visible.unwrap_or(true)));
+        glfw.window_hint(glfw::WindowHint::TransparentFramebuffer(
+            builder.transparent.unwrap_or(false),
+        ));
+
+        let inner_size = builder.inner_size.unwrap_or(egui::vec2(400.0, 300.0));
+        let created = root_window.create_shared(
+            (inner_size.x * screen_coords_per_point).round() as u32,
+            (inner_size.y * screen_coords_per_point).round() as u32,
+            builder.title.as_deref().unwrap_or("egui"),
+            glfw::WindowMode::Windowed,
+        );
+
+        // restore the hints to their defaults so that windows created
+        // by the application are not affected
+        glfw.window_hint(glfw::WindowHint::Decorated(true));
+        glfw.window_hint(glfw::WindowHint::Resizable(true));
+        glfw.window_hint(glfw::WindowHint::Visible(true));
+        glfw.window_hint(glfw::WindowHint::TransparentFramebuffer(false));
+
+        let (mut window, events) = created?;
+        if let Some(position) = builder.position {
+            window.set_pos(
+                (position.x * screen_coords_per_point).round() as i32,
+                (position.y * screen_coords_per_point).round() as i32,
+            );
+        }
+        window.set_all_polling(true);
+        window.set_store_lock_key_mods(true);
+        window.make_current();
+        // multiple windows waiting on vsync would slow down the
+        // application
+        glfw.set_swap_interval(glfw::SwapInterval::None);
+
+        let pixels_per_point = window.get_content_scale().0;
+        let mut input = Input::for_viewport(viewport_id, pixels_per_point);
+        input.set_screen_rect(&window, pixels_per_point);
+
+        Some(Self {
+            imm: GPUImmediate::new(),
+            window,
+            events,
+            input,
+            builder,
+            viewport_ui_cb: None,
+            pixels_per_point,
+            command_state: WindowCommandState::default(),
+        })
+    }
+
+    /// Process the pending events of the window.
+    pub fn handle_events(&mut self) {
+        let Self {
+            window,
+            events,
+            input,
+            pixels_per_point,
+            ..
+        } = self;
+        glfw::flush_messages(events).for_each(|(_, event)| match event {
+            event @ glfw::WindowEvent::Close => {
+                // egui decides if the viewport is closed
+                window.set_should_close(false);
+                input.handle_event(&event, window, *pixels_per_point);
+            }
+            // the screen rect is set before each pass, the OpenGL
+            // viewport is set before painting since the window's
+            // context need not be current
+            glfw::WindowEvent::FramebufferSize(_, _) => {}
+            event => input.handle_event(&event, window, *pixels_per_point),
+        });
+    }
+
+    /// Apply the changes of the `builder` of the viewport to the
+    /// window.
+    ///
+    /// Returns `true` if the window must be recreated to apply the
+    /// changes.
+    pub fn patch_builder(&mut self, builder: egui::ViewportBuilder) -> bool {
+        let (commands, recreate) = self.builder.patch(builder);
+        commands
+            .into_iter()
+            .for_each(|command| self.apply_command(command));
+        recreate
+    }
+
+    /// Apply the [`egui::ViewportCommand`] to the window.
+    pub fn apply_command(&mut self, command: egui::ViewportCommand) {
+        let unsupported = match command {
+            egui::ViewportCommand::Close => {
+                self.input.push_viewport_event(egui::ViewportEvent::Close);
+                None
+            }
+            egui::ViewportCommand::RequestCut => {
+                self.input.push_event(egui::Event::Cut);
+                None
+            }
+            egui::ViewportCommand::RequestCopy => {
+                self.input.push_event(egui::Event::Copy);
+                None
+            }
+            command => apply_window_command(
+                &mut self.window,
+                &mut self.command_state,
+                self.pixels_per_point,
+                command,
+            ),
+        };
+
+        #[cfg(feature = "tracing")]
+        if let Some(command) = unsupported {
+            tracing::warn!("unsupported viewport command: {:?}", command);
+        }
+        #[cfg(not(feature = "tracing"))]
+        let _ = unsupported;
+    }
+
+    /// Apply the [`egui::CursorIcon`] of the last pass to the window.
+    pub fn apply_cursor_icon(&mut self, cursor_icon: egui::CursorIcon) {
+        apply_cursor_icon(&mut self.window, &mut self.command_state, cursor_icon);
+    }
+
+    /// Get the [`egui::RawInput`] for the next pass of the viewport.
+    pub fn take_raw_input(&mut self) -> egui::RawInput {
+        self.handle_events();
+        self.input
+            .set_screen_rect(&self.window, self.pixels_per_point);
+        self.input
+            .update_viewport_info(&self.window, self.pixels_per_point);
+        self.input.take()
+    }
+
+    /// Paint the window, `paint` is called with the window's OpenGL
+    /// context current and the framebuffer size in pixels.
+    pub fn paint(&mut self, clear_colour: egui::Rgba, paint: impl FnOnce(&mut Self, (f32, f32))) {
+        self.window.make_current();
+        let (width, height) = self.window.get_framebuffer_size();
+        unsafe {
+            gl::Viewport(0, 0, width, height);
+            // the clear colour is linear, convert it to sRGB
+            gl::Enable(gl::FRAMEBUFFER_SRGB);
+            gl::ClearColor(
+                clear_colour.r(),
+                clear_colour.g(),
+                clear_colour.b(),
+                clear_colour.a(),
+            );
+            gl::Clear(gl::COLOR_BUFFER_BIT);
+        }
+        paint(self, (width as f32, height as f32));
+        self.window.swap_buffers();
+    }
+}
+
+impl Drop for ViewportWindow {
+    fn drop(&mut self) {
+        // the vertex array objects of `imm` must be deleted with the
+        // window's context current, `imm` is dropped before the
+        // window
+        self.window.make_current();
+    }
+}
+
+/// Output of the pass of an immediate viewport, painted after the
+/// root viewport's pass ends.
+pub struct ImmediateOutput {
+    /// Viewport of the pass.
+    pub viewport_id: egui::ViewportId,
+    /// Tessellated shapes of the pass.
+    pub primitives: Vec<egui::ClippedPrimitive>,
+    /// Changes to the textures in the pass.
+    pub textures_delta: egui::TexturesDelta,
+    /// Pixels per point of the pass.
+    pub pixels_per_point: f32,
+    /// Platform output of the pass.
+    pub platform_output: egui::PlatformOutput,
+    /// Cursor icon of the pass, kept apart from the platform output
+    /// which is handed to the application.
+    pub cursor_icon: egui::CursorIcon,
+}
+
+/// Native windows of the egui viewports other than the root
+/// viewport.
+///
+/// Shared with the immediate viewport renderer (see
+/// [`egui::Context::set_immediate_viewport_renderer()`]) which is
+/// called while the root viewport's pass runs.
+#[derive(Default)]
+pub struct Viewports {
+    /// Windows of the viewports.
+    pub windows: egui::ViewportIdMap<ViewportWindow>,
+    /// Outputs of the immediate viewports' passes that are yet to be
+    /// painted.
+    pub immediate_outputs: Vec<ImmediateOutput>,
+    /// Outputs of the viewports from the last root pass.
+    pub viewport_outputs: egui::ViewportIdMap<egui::ViewportOutput>,
+    /// Textures freed by the passes that are yet to be deleted, they
+    /// are deleted after all the viewports are painted.
+    pub textures_to_free: Vec<egui::TextureId>,
+    /// Time of the current pass.
+    pub time: f64,
+    /// Modifier key used for [`egui::Modifiers::command`] by the
+    /// windows.
+    pub command_modifier: CommandModifier,
+    /// How the scroll deltas are sent to egui by the windows.
+    pub scroll_config: ScrollConfig,
+}
+
+impl Viewports {
+    /// Get the [`egui::RawInput`] for the next pass of the viewport
+    /// `viewport_id`.
+    ///
+    /// If the viewport's window is not created yet, the screen rect
+    /// is taken from `builder`.
+    pub fn take_raw_input(
+        &mut self,
+        viewport_id: egui::ViewportId,
+        builder: &egui::ViewportBuilder,
+    ) -> egui::RawInput {
+        let mut raw_input = match self.windows.get_mut(&viewport_id) {
+            Some(viewport_window) => viewport_window.take_raw_input(),
+            None => egui::RawInput {
+                viewport_id,
+                screen_rect: builder
+                    .inner_size
+                    .map(|size| egui::Rect::from_min_size(egui::Pos2::ZERO, size)),
+                // egui expects the info of the viewport of the pass
+                viewports: std::iter::once((viewport_id, egui::ViewportInfo::default())).collect(),
+                ..Default::default()
+            },
+        };
+        raw_input.time = Some(self.time);
+        raw_input
+    }
+}
+
+/// Backend state an immediate viewport of an egui context is
+/// rendered with, see [`register_immediate_viewport_renderer()`].
+#[derive(Clone)]
+struct ImmediateViewportBackend {
+    /// Context whose immediate viewports are rendered.
+    egui_ctx: egui::Context,
+    /// Viewports of the backend, the renderer must not keep the
+    /// windows alive.
+    viewports: Weak<RefCell<Viewports>>,
+    /// Clipboard of the backend.
+    clipboard: Weak<RefCell<ClipboardIntegration>>,
+}
+
+thread_local! {
+    /// Backends of the contexts with multiple viewports enabled on
+    /// this thread. The immediate viewport renderer of egui is a
+    /// thread local global, so a single renderer looks up the
+    /// backend of the context it is called for.
+    static IMMEDIATE_VIEWPORT_BACKENDS: RefCell<Vec<ImmediateViewportBackend>> =
+        const { RefCell::new(Vec::new()) };
+}
+
+/// Render the immediate viewports of `egui_ctx` with `viewports`
+/// and `clipboard`. Multiple backends can be registered on the same
+/// thread, each context renders with its own backend.
+pub fn register_immediate_viewport_renderer(
+    egui_ctx: &egui::Context,
+    viewports: Weak<RefCell<Viewports>>,
+    clipboard: Weak<RefCell<ClipboardIntegration>>,
+) {
+    IMMEDIATE_VIEWPORT_BACKENDS.with(|backends| {
+        let mut backends = backends.borrow_mut();
+        backends.retain(|backend| {
+            backend.viewports.strong_count() > 0 && backend.egui_ctx != *egui_ctx
+        });
+        backends.push(ImmediateViewportBackend {
+            egui_ctx: egui_ctx.clone(),
+            viewports,
+            clipboard,
+        });
+    });
+    egui::Context::set_immediate_viewport_renderer(render_immediate_viewport);
+}
+
+/// Get the backend registered for `egui_ctx`, see
+/// [`register_immediate_viewport_renderer()`]. The backends that
+/// were dropped are forgotten.
+fn find_immediate_viewport_backend(egui_ctx: &egui::Context) -> Option<ImmediateViewportBackend> {
+    IMMEDIATE_VIEWPORT_BACKENDS.with(|backends| {
+        let mut backends = backends.borrow_mut();
+        backends.retain(|backend| backend.viewports.strong_count() > 0);
+        backends
+            .iter()
+            .find(|backend| backend.egui_ctx == *egui_ctx)
+            .cloned()
+    })
+}
+
+/// Run the pass of the immediate viewport, its output is painted
+/// after the root viewport's pass ends, see
+/// [`crate::EguiBackend::update_viewports()`].
+fn render_immediate_viewport(ctx: &egui::Context, immediate_viewport: egui::ImmediateViewport) {
+    let egui::ImmediateViewport {
+        ids,
+        builder,
+        mut viewport_ui_cb,
+    } = immediate_viewport;
+    let Some((viewports, clipboard)) = find_immediate_viewport_backend(ctx)
+        .and_then(|backend| Some((backend.viewports.upgrade()?, backend.clipboard)))
+    else {
+        viewport_ui_cb(ctx);
+        return;
+    };
+
+    // the borrow must not be held while the pass runs since nested
+    // immediate viewports need it too
+    let mut raw_input = viewports.borrow_mut().take_raw_input(ids.this, &builder);
+    if let Some(clipboard) = clipboard.upgrade() {
+        clipboard.borrow_mut().process_input(&mut raw_input);
+    }
+    let full_output = ctx.run(raw_input, |ctx| viewport_ui_cb(ctx));
+
+    let primitives = ctx.tessellate(full_output.shapes, full_output.pixels_per_point);
+    viewports
+        .borrow_mut()
+        .immediate_outputs
+        .push(ImmediateOutput {
+            viewport_id: ids.this,
+            primitives,
+            textures_delta: full_output.textures_delta,
+            pixels_per_point: full_output.pixels_per_point,
+            cursor_icon: full_output.platform_output.cursor_icon,
+            platform_output: full_output.platform_output,
+        });
+}
+
+/// State of a window needed to apply [`egui::ViewportCommand`]s and
+/// [`egui::CursorIcon`]s, see [`apply_window_command()`] and
+/// [`apply_cursor_icon()`].
+#[derive(Debug, Default)]
+pub struct WindowCommandState {
+    /// Minimum inner size of the window in points, GLFW sets the
+    /// minimum and maximum together.
+    min_inner_size: Option<egui::Vec2>,
+    /// Maximum inner size of the window in points.
+    max_inner_size: Option<egui::Vec2>,
+    /// Position and size of the window (in screen coordinates)
+    /// before it was made fullscreen.
+    windowed_rect: Option<((i32, i32), (i32, i32))>,
+    /// Is the cursor hidden.
+    cursor_hidden: bool,
+    /// Is the cursor grabbed.
+    cursor_grabbed: bool,
+    /// Icon of the cursor of the window, [`None`] until the first
+    /// icon is applied.
+    cursor_icon: Option<egui::CursorIcon>,
+    /// Standard cursor set on the window, [`None`] for the default
+    /// cursor.
+    standard_cursor: Option<glfw::StandardCursor>,
+    /// Standard cursors that are created but not set on the window,
+    /// they are reused instead of being created for every change.
+    cursors: AHashMap<glfw::StandardCursor, glfw::Cursor>,
+}
+
+impl WindowCommandState {
+    /// Get the [`glfw::CursorMode`] for the current cursor state.
+    fn cursor_mode(&self) -> glfw::CursorMode {
+        if self.cursor_grabbed {
+            glfw::CursorMode::Disabled
+        } else if self.cursor_hidden || self.cursor_icon == Some(egui::CursorIcon::None) {
+            glfw::CursorMode::Hidden
+        } else {
+            glfw::CursorMode::Normal
+        }
+    }
+}
+
+/// Apply the [`egui::ViewportCommand`] that affects only the window
+/// to `window`.
+///
+/// Returns the command back if it is not supported by GLFW or is not
+/// a window command (for example, [`egui::ViewportCommand::Close`]
+/// whose meaning depends on the viewport).
+pub fn apply_window_command(
+    window: &mut glfw::Window,
+    state: &mut WindowCommandState,
+    pixels_per_point: f32,
+    command: egui::ViewportCommand,
+) -> Option<egui::ViewportCommand> {
+    let screen_coords_per_point = pixels_per_point / pixels_per_screen_coord(window);
+    let to_screen_coords = |points: f32| (points * screen_coords_per_point).round() as i32;
+
+    match command {
+        egui::ViewportCommand::CancelClose => window.set_should_close(false),
+        egui::ViewportCommand::Title(title) => window.set_title(&title),
+        egui::ViewportCommand::Visible(true) => window.show(),
+        egui::ViewportCommand::Visible(false) => window.hide(),
+        egui::ViewportCommand::OuterPosition(position) => {
+            // GLFW positions the content area of the window
+            let (left, top, _, _) = window.get_frame_size();
+            window.set_pos(
+                to_screen_coords(position.x) + left,
+                to_screen_coords(position.y) + top,
+            );
+        }
+        egui::ViewportCommand::InnerSize(size) => {
+            window.set_size(to_screen_coords(size.x), to_screen_coords(size.y))
+        }
+        egui::ViewportCommand::MinInnerSize(size) => {
+            state.min_inner_size = Some(size);
+            set_size_limits(window, state, screen_coords_per_point);
+        }
+        egui::ViewportCommand::MaxInnerSize(size) => {
+            state.max_inner_size = Some(size);
+            set_size_limits(window, state, screen_coords_per_point);
+        }
+        egui::ViewportCommand::Resizable(resizable) => window.set_resizable(resizable),
+        egui::ViewportCommand::Minimized(true) => window.iconify(),
+        egui::ViewportCommand::Maximized(true) => window.maximize(),
+        egui::ViewportCommand::Minimized(false) | egui::ViewportCommand::Maximized(false) => {
+            window.restore()
+        }
+        egui::ViewportCommand::Fullscreen(true) => {
+            if state.windowed_rect.is_none() {
+                let (width, height) = window.get_size();
+                state.windowed_rect = Some((window.get_pos(), (width, height)));
+                let mut glfw = window.glfw.clone();
+                glfw.with_primary_monitor(|_, monitor| {
+                    let Some(monitor) = monitor else {
+                        return;
+                    };
+                    let Some(video_mode) = monitor.get_video_mode() else {
+                        return;
+                    };
+                    window.set_monitor(
+                        glfw::WindowMode::FullScreen(monitor),
+                        0,
+                        0,
+                        video_mode.width,
+                        video_mode.height,
+                        Some(video_mode.refresh_rate),
+                    );
+                });
+            }
+        }
+        egui::ViewportCommand::Fullscreen(false) => {
+            if let Some(((x, y), (width, height))) = state.windowed_rect.take() {
+                window.set_monitor(
+                    glfw::WindowMode::Windowed,
+                    x,
+                    y,
+                    width.max(1) as u32,
+                    height.max(1) as u32,
+                    None,
+                );
+            }
+        }
+        egui::ViewportCommand::Decorations(decorated) => window.set_decorated(decorated),
+        egui::ViewportCommand::WindowLevel(egui::WindowLevel::Normal) => window.set_floating(false),
+        egui::ViewportCommand::WindowLevel(egui::WindowLevel::AlwaysOnTop) => {
+            window.set_floating(true)
+        }
+        egui::ViewportCommand::Icon(icon) => window.set_icon_from_pixels(
+            icon.map(|icon| {
+                vec![glfw::PixelImage {
+                    width: icon.width,
+                    height: icon.height,
+                    // GLFW reads the bytes of the pixels as RGBA
+                    pixels: icon
+                        .rgba
+                        .chunks_exact(4)
+                        .map(|pixel| u32::from_ne_bytes([pixel[0], pixel[1], pixel[2], pixel[3]]))
+                        .collect(),
+                }]
+            })
+            .unwrap_or_default(),
+        ),
+        egui::ViewportCommand::Focus => window.focus(),
+        egui::ViewportCommand::RequestUserAttention(egui::UserAttentionType::Reset) => {}
+        egui::ViewportCommand::RequestUserAttention(_) => window.request_attention(),
+        egui::ViewportCommand::CursorPosition(position) => window.set_cursor_pos(
+            (position.x * screen_coords_per_point).into(),
+            (position.y * screen_coords_per_point).into(),
+        ),
+        egui::ViewportCommand::CursorGrab(egui::CursorGrab::None) => {
+            state.cursor_grabbed = false;
+            window.set_cursor_mode(state.cursor_mode());
+        }
+        egui::ViewportCommand::CursorGrab(egui::CursorGrab::Locked) => {
+            state.cursor_grabbed = true;
+            window.set_cursor_mode(state.cursor_mode());
+        }
+        egui::ViewportCommand::CursorVisible(visible) => {
+            state.cursor_hidden = !visible;
+            window.set_cursor_mode(state.cursor_mode());
+        }
+        command => return Some(command),
+    }
+    None
+}
+
+/// Set the size limits of the window from the [`WindowCommandState`].
+fn set_size_limits(
+    window: &mut glfw::Window,
+    state: &WindowCommandState,
+    screen_coords_per_point: f32,
+) {
+    let to_size_limit = |points: Option<f32>| {
+        points.map(|points| (points * screen_coords_per_point).round() as u32)
+    };
+    window.set_size_limits(
+        to_size_limit(state.min_inner_size.map(|size| size.x)),
+        to_size_limit(state.min_inner_size.map(|size| size.y)),
+        to_size_limit(state.max_inner_size.map(|size| size.x)),
+        to_size_limit(state.max_inner_size.map(|size| size.y)),
+    );
+}
+
+/// Get the GLFW standard cursor for the [`egui::CursorIcon`],
+/// [`None`] for the default cursor.
+///
+/// GLFW has only a few standard cursors, the closest one is used for
+/// the rest of the icons or the default cursor if none is close.
+fn standard_cursor(cursor_icon: egui::CursorIcon) -> Option<glfw::StandardCursor> {
+    match cursor_icon {
+        egui::CursorIcon::Default
+        | egui::CursorIcon::None
+        | egui::CursorIcon::ContextMenu
+        | egui::CursorIcon::Help
+        | egui::CursorIcon::Progress
+        | egui::CursorIcon::Wait
+        | egui::CursorIcon::Alias
+        | egui::CursorIcon::Copy
+        | egui::CursorIcon::NoDrop
+        | egui::CursorIcon::NotAllowed
+        | egui::CursorIcon::ZoomIn
+        | egui::CursorIcon::ZoomOut
+        // no cell, move, diagonal resize or not allowed cursors in
+        // GLFW 3.3, the arrow is less misleading than the crosshair
+        | egui::CursorIcon::Cell
+        | egui::CursorIcon::Move
+        | egui::CursorIcon::AllScroll
+        | egui::CursorIcon::ResizeNeSw
+        | egui::CursorIcon::ResizeNwSe
+        | egui::CursorIcon::ResizeNorthEast
+        | egui::CursorIcon::ResizeNorthWest
+        | egui::CursorIcon::ResizeSouthEast
+        | egui::CursorIcon::ResizeSouthWest => None,
+        egui::CursorIcon::PointingHand | egui::CursorIcon::Grab | egui::CursorIcon::Grabbing => {
+            Some(glfw::StandardCursor::Hand)
+        }
+        egui::CursorIcon::Text | egui::CursorIcon::VerticalText => {
+            Some(glfw::StandardCursor::IBeam)
+        }
+        egui::CursorIcon::ResizeHorizontal
+        | egui::CursorIcon::ResizeColumn
+        | egui::CursorIcon::ResizeEast
+        | egui::CursorIcon::ResizeWest => Some(glfw::StandardCursor::HResize),
+        egui::CursorIcon::ResizeVertical
+        | egui::CursorIcon::ResizeRow
+        | egui::CursorIcon::ResizeNorth
+        | egui::CursorIcon::ResizeSouth => Some(glfw::StandardCursor::VResize),
+        egui::CursorIcon::Crosshair => Some(glfw::StandardCursor::Crosshair),
+    }
+}
+
+/// Apply the [`egui::CursorIcon`] to `window`, the cursor is hidden
+/// for [`egui::CursorIcon::None`]. Does nothing if the icon did not
+/// change.
+pub fn apply_cursor_icon(
+    window: &mut glfw::Window,
+    state: &mut WindowCommandState,
+    cursor_icon: egui::CursorIcon,
+) {
+    if state.cursor_icon == Some(cursor_icon) {
+        return;
+    }
+    state.cursor_icon = Some(cursor_icon);
+    window.set_cursor_mode(state.cursor_mode());
+
+    let standard_cursor = standard_cursor(cursor_icon);
+    if cursor_icon == egui::CursorIcon::None || state.standard_cursor == standard_cursor {
+        return;
+    }
+    let cursor = standard_cursor.map(|standard_cursor| {
+        state
+            .cursors
+            .remove(&standard_cursor)
+            .unwrap_or_else(|| glfw::Cursor::standard(standard_cursor))
+    });
+    // keep the previous cursor for later
+    if let (Some(previous_standard_cursor), Some(previous_cursor)) =
+        (state.standard_cursor, window.set_cursor(cursor))
+    {
+        state
+            .cursors
+            .insert(previous_standard_cursor, previous_cursor);
+    }
+    state.standard_cursor = standard_cursor;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::*;
+
+    /// Every [`egui::CursorIcon`] along with its GLFW cursor, [`None`]
+    /// for the arrow.
+    const CURSORS: &[(egui::CursorIcon, Option<glfw::StandardCursor>)] = &[
+        (egui::CursorIcon::Default, None),
+        (egui::CursorIcon::None, None),
+        (egui::CursorIcon::ContextMenu, None),
+        (egui::CursorIcon::Help, None),
+        (
+            egui::CursorIcon::PointingHand,
+            Some(glfw::StandardCursor::Hand),
+        ),
+        (egui::CursorIcon::Progress, None),
+        (egui::CursorIcon::Wait, None),
+        (egui::CursorIcon::Cell, None),
+        (
+            egui::CursorIcon::Crosshair,
+            Some(glfw::StandardCursor::Crosshair),
+        ),
+        (egui::CursorIcon::Text, Some(glfw::StandardCursor::IBeam)),
+        (
+            egui::CursorIcon::VerticalText,
+            Some(glfw::StandardCursor::IBeam),
+        ),
+        (egui::CursorIcon::Alias, None),
+        (egui::CursorIcon::Copy, None),
+        (egui::CursorIcon::Move, None),
+        (egui::CursorIcon::NoDrop, None),
+        (egui::CursorIcon::NotAllowed, None),
+        (egui::CursorIcon::Grab, Some(glfw::StandardCursor::Hand)),
+        (egui::CursorIcon::Grabbing, Some(glfw::StandardCursor::Hand)),
+        (egui::CursorIcon::AllScroll, None),
+        (
+            egui::CursorIcon::ResizeHorizontal,
+            Some(glfw::StandardCursor::HResize),
+        ),
+        (egui::CursorIcon::ResizeNeSw, None),
+        (egui::CursorIcon::ResizeNwSe, None),
+        (
+            egui::CursorIcon::ResizeVertical,
+            Some(glfw::StandardCursor::VResize),
+        ),
+        (
+            egui::CursorIcon::ResizeEast,
+            Some(glfw::StandardCursor::HResize),
+        ),
+        (egui::CursorIcon::ResizeSouthEast, None),
+        (
+            egui::CursorIcon::ResizeSouth,
+            Some(glfw::StandardCursor::VResize),
+        ),
+        (egui::CursorIcon::ResizeSouthWest, None),
+        (
+            egui::CursorIcon::ResizeWest,
+            Some(glfw::StandardCursor::HResize),
+        ),
+        (egui::CursorIcon::ResizeNorthWest, None),
+        (
+            egui::CursorIcon::ResizeNorth,
+            Some(glfw::StandardCursor::VResize),
+        ),
+        (egui::CursorIcon::ResizeNorthEast, None),
+        (
+            egui::CursorIcon::ResizeColumn,
+            Some(glfw::StandardCursor::HResize),
+        ),
+        (
+            egui::CursorIcon::ResizeRow,
+            Some(glfw::StandardCursor::VResize),
+        ),
+        (egui::CursorIcon::ZoomIn, None),
+        (egui::CursorIcon::ZoomOut, None),
+    ];
+
+    #[test]
+    fn every_cursor_icon_is_covered() {
+        assert_eq!(CURSORS.len(), egui::CursorIcon::ALL.len());
+        egui::CursorIcon::ALL.iter().for_each(|cursor_icon| {
+            assert!(
+                CURSORS.iter().any(|(icon, _)| icon == cursor_icon),
+                "{:?}",
+                cursor_icon
+            );
+        });
+    }
+
+    #[test]
+    fn standard_cursors() {
+        CURSORS.iter().for_each(|(cursor_icon, cursor)| {
+            assert_eq!(standard_cursor(*cursor_icon), *cursor, "{:?}", cursor_icon);
+        });
+    }
+
+    /// Run a pass of `egui_ctx` that shows an immediate viewport.
+    fn show_immediate_viewport(egui_ctx: &egui::Context) {
+        let _ = egui_ctx.run(egui::RawInput::default(), |ctx| {
+            ctx.show_viewport_immediate(
+                egui::ViewportId::from_hash_of("immediate"),
+                egui::ViewportBuilder::default().with_inner_size([100.0, 100.0]),
+                |_, _| {},
+            );
+        });
+    }
+
+    #[test]
+    fn immediate_viewports_use_the_backend_of_their_context() {
+        let clipboard = Rc::new(RefCell::new(ClipboardIntegration::default()));
+        let backends = [(); 2].map(|_| {
+            let egui_ctx = egui::Context::default();
+            egui_ctx.set_embed_viewports(false);
+            let viewports = Rc::new(RefCell::new(Viewports::default()));
+            register_immediate_viewport_renderer(
+                &egui_ctx,
+                Rc::downgrade(&viewports),
+                Rc::downgrade(&clipboard),
+            );
+            (egui_ctx, viewports)
+        });
+        let immediate_output_len =
+            |index: usize| backends[index].1.borrow().immediate_outputs.len();
+
+        show_immediate_viewport(&backends[0].0);
+        assert_eq!((immediate_output_len(0), immediate_output_len(1)), (1, 0));
+
+        show_immediate_viewport(&backends[1].0);
+        show_immediate_viewport(&backends[1].0);
+        assert_eq!((immediate_output_len(0), immediate_output_len(1)), (1, 2));
+    }
+
+    #[test]
+    fn dropped_backends_are_forgotten() {
+        let egui_ctx = egui::Context::default();
+        let viewports = Rc::new(RefCell::new(Viewports::default()));
+        register_immediate_viewport_renderer(&egui_ctx, Rc::downgrade(&viewports), Weak::new());
+        assert!(find_immediate_viewport_backend(&egui_ctx).is_some());
+
+        drop(viewports);
+        assert!(find_immediate_viewport_backend(&egui_ctx).is_none());
+        IMMEDIATE_VIEWPORT_BACKENDS.with(|backends| assert!(backends.borrow().is_empty()));
+    }
+}